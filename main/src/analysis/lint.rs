@@ -0,0 +1,141 @@
+use fxhash::FxHashSet;
+
+use crate::asm::ASM;
+use crate::asm::blocks::Func;
+use crate::asm::instr::{InstrKind, Operand, Temp, TempID};
+use crate::warning::{Lint, Warning};
+
+use super::liveness::liveness;
+
+
+/// Set of temps read anywhere in the function
+fn used_temps(func: &Func) -> FxHashSet<&Temp> {
+  let mut uses = FxHashSet::default();
+  for block in func.blocks.values() {
+    let srcs = block.lines.iter()
+      .flat_map(|instr| instr.srcs())
+      .chain(block.branch.kind.srcs());
+
+    for src in srcs {
+      if let Operand::Temp(temp) = src {
+        uses.insert(temp);
+      }
+    }
+  }
+
+  uses
+}
+
+fn unreachable_blocks(func: &Func, warnings: &mut Vec<Warning>) {
+  let mut visited = set!(func.entry());
+  let mut stack = vec![func.entry()];
+
+  while let Some(bid) = stack.pop() {
+    if let Some(block) = func.blocks.get(&bid) {
+      for succ in block.succs() {
+        if visited.insert(succ) {
+          stack.push(succ);
+        }
+      }
+    }
+  }
+
+  for (bid, block) in func.blocks.iter() {
+    if !visited.contains(bid) {
+      warnings.push(Warning::UnreachableBlock(func.name.clone(), func.label(*bid), block.line_start));
+    }
+  }
+}
+
+fn unused_dests(func: &Func, warnings: &mut Vec<Warning>) {
+  let uses = used_temps(func);
+
+  for block in func.blocks.values() {
+    for instr in block.lines.iter() {
+      match instr.dest() {
+        Some(dest) if !uses.contains(dest) => warnings.push(match dest.0 {
+          TempID::Num(_) => Warning::UnusedTemp(dest.clone(), instr.line),
+          TempID::Reg(_) => Warning::DeadRegWrite(dest.clone(), instr.line),
+        }),
+        _ => {},
+      }
+    }
+  }
+}
+
+/// Stores that are dead even though the temp is read elsewhere
+/// Temps that are never read are already covered by `unused_dests`
+fn dead_stores(func: &Func, warnings: &mut Vec<Warning>) {
+  let uses = used_temps(func);
+
+  for instr in liveness(func).dead_stores(func) {
+    match instr.dest() {
+      Some(dest) if uses.contains(dest) => warnings.push(Warning::DeadStore(dest.clone(), instr.line)),
+      _ => {},
+    }
+  }
+}
+
+fn nop_sequences(func: &Func, warnings: &mut Vec<Warning>) {
+  for block in func.blocks.values() {
+    let mut run: Option<(u64, u64)> = None;
+
+    for instr in block.lines.iter() {
+      match (&instr.kind, run.as_mut()) {
+        (InstrKind::Nop, Some((_, count))) => *count += 1,
+        (InstrKind::Nop, None) => run = Some((instr.line, 1)),
+        (_, _) => if let Some((line, count)) = run.take() {
+          warnings.push(Warning::NopSequence(line, count));
+        },
+      }
+    }
+
+    if let Some((line, count)) = run {
+      warnings.push(Warning::NopSequence(line, count));
+    }
+  }
+}
+
+/// Only blocks with phis, whose sources follow the order of the predecessors
+fn pred_order(func: &Func, warnings: &mut Vec<Warning>) {
+  for (bid, block) in func.blocks.iter() {
+    if !block.lines.iter().any(|instr| matches!(instr.kind, InstrKind::Phi { .. })) {
+      continue;
+    }
+
+    let starts: Vec<_> = block.preds.iter()
+      .filter_map(|pred| func.blocks.get(pred).map(|pred| pred.line_start))
+      .collect();
+
+    if starts.windows(2).any(|pair| pair[0] > pair[1]) {
+      warnings.push(Warning::PredOrder(func.label(*bid), block.line_start));
+    }
+  }
+}
+
+
+/// Pass Reporting the Warnings of Some Lints in a Function
+type Pass = fn(&Func, &mut Vec<Warning>);
+
+/// Collect the warnings of every enabled lint for the program, ordered by line
+/// Passes whose lints are all disabled are skipped
+pub fn lint(abs: &ASM, enabled: &dyn Fn(Lint) -> bool, warnings: &mut Vec<Warning>) {
+  let start = warnings.len();
+  let passes: &[(&[Lint], Pass)] = &[
+    (&[Lint::UnreachableBlock], unreachable_blocks),
+    (&[Lint::UnusedTemp, Lint::DeadRegWrite], unused_dests),
+    (&[Lint::DeadStore], dead_stores),
+    (&[Lint::NopSequence], nop_sequences),
+    (&[Lint::PredOrder], pred_order),
+  ];
+
+  for func in abs.values() {
+    for (lints, pass) in passes.iter() {
+      if lints.iter().any(|lint| enabled(*lint)) {
+        pass(func, warnings);
+      }
+    }
+  }
+
+  warnings[start..].sort_by_key(|warning| warning.line());
+}
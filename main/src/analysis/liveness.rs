@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+
+use crate::asm::blocks::{BasicBlock, BlockID, Func};
+use crate::asm::instr::{Instr, InstrKind, Operand, Temp};
+
+
+/// Live Temps at the Boundaries of Each Block
+/// Phi functions define their destination at the top of the block and read
+/// their sources at the end of the matching predecessor, so a phi source
+/// is live out of its predecessor but never live into the phi's own block
+pub struct Liveness {
+  pub live_in: FxHashMap<BlockID, FxHashSet<Temp>>,
+  pub live_out: FxHashMap<BlockID, FxHashSet<Temp>>,
+}
+
+fn add_temps<'a>(live: &mut FxHashSet<Temp>, srcs: impl IntoIterator<Item = &'a Operand>) {
+  for src in srcs {
+    if let Operand::Temp(temp) = src {
+      live.insert(temp.clone());
+    }
+  }
+}
+
+impl Liveness {
+  /// Temps live along the edge from `pred` into `succ`
+  fn edge(&self, func: &Func, pred: BlockID, succ: BlockID) -> FxHashSet<Temp> {
+    let mut live = self.live_in.get(&succ).cloned().unwrap_or_default();
+
+    if let Some(block) = func.blocks.get(&succ) {
+      if let Some(idx) = block.preds.iter().position(|&x| x == pred) {
+        for instr in block.lines.iter() {
+          if let InstrKind::Phi { srcs, .. } = &instr.kind {
+            add_temps(&mut live, srcs.get(idx));
+          }
+        }
+      }
+    }
+
+    live
+  }
+
+  /// Walk the block backwards, calling `visit` with each instruction and
+  /// the temps live immediately after it. Returns the temps live into the block.
+  fn scan<'a>(&self, func: &Func, block: &'a BasicBlock, mut visit: impl FnMut(&'a Instr, &FxHashSet<Temp>)) -> FxHashSet<Temp> {
+    let mut live = FxHashSet::default();
+    for succ in block.branch.kind.targets() {
+      live.extend(self.edge(func, block.id, succ));
+    }
+    add_temps(&mut live, block.branch.kind.srcs());
+
+    for instr in block.lines.iter().rev() {
+      visit(instr, &live);
+
+      match &instr.kind {
+        InstrKind::If { cond, block: target } => {
+          live.extend(self.edge(func, block.id, *target));
+          add_temps(&mut live, [cond]);
+        },
+
+        InstrKind::Phi { dest, .. } => {
+          live.remove(dest);
+        },
+
+        _ => {
+          if let Some(dest) = instr.dest() {
+            live.remove(dest);
+          }
+          add_temps(&mut live, instr.srcs());
+        },
+      }
+    }
+
+    live
+  }
+
+  /// Calls `visit` with every instruction of the function and the temps live immediately after it
+  pub fn for_each_instr<'a>(&self, func: &'a Func, mut visit: impl FnMut(&'a BasicBlock, &'a Instr, &FxHashSet<Temp>)) {
+    for block in func.blocks.values() {
+      self.scan(func, block, |instr, live| visit(block, instr, live));
+    }
+  }
+
+  /// Instructions whose destination is not live immediately after them
+  pub fn dead_stores<'a>(&self, func: &'a Func) -> Vec<&'a Instr> {
+    let mut dead = vec![];
+    self.for_each_instr(func, |_, instr, live| {
+      if matches!(instr.dest(), Some(dest) if !live.contains(dest)) {
+        dead.push(instr);
+      }
+    });
+
+    dead.sort_by_key(|instr| instr.line);
+    dead
+  }
+
+  /// Print the live-in and live-out sets of every block in source order
+  pub fn report(&self, func: &Func) -> String {
+    let mut out = String::new();
+    let empty = FxHashSet::default();
+    writeln!(out, "{}", func.name).unwrap();
+
+    for block in func.blocks.values() {
+      let live_in = self.live_in.get(&block.id).unwrap_or(&empty);
+      let live_out = self.live_out.get(&block.id).unwrap_or(&empty);
+
+      writeln!(out, "  {}", func.label(block.id)).unwrap();
+      writeln!(out, "    in:  {}", live_in.iter().sorted().format(" ")).unwrap();
+      writeln!(out, "    out: {}", live_out.iter().sorted().format(" ")).unwrap();
+    }
+
+    out
+  }
+}
+
+
+/// Backwards dataflow analysis of the temps live at each block boundary
+pub fn liveness(func: &Func) -> Liveness {
+  let mut live = Liveness {
+    live_in: func.blocks.keys().map(|bid| (*bid, FxHashSet::default())).collect(),
+    live_out: FxHashMap::default(),
+  };
+
+  // Iterate to a Fixed Point
+  let mut changed = true;
+  while changed {
+    changed = false;
+
+    for block in func.blocks.values() {
+      let live_in = live.scan(func, block, |_, _| {});
+      if live.live_in.get(&block.id) != Some(&live_in) {
+        live.live_in.insert(block.id, live_in);
+        changed = true;
+      }
+    }
+  }
+
+  for block in func.blocks.values() {
+    let mut live_out = FxHashSet::default();
+    for succ in block.succs() {
+      live_out.extend(live.edge(func, block.id, succ));
+    }
+
+    live.live_out.insert(block.id, live_out);
+  }
+
+  live
+}
@@ -1,10 +1,15 @@
 mod ssa;
+mod lint;
+pub mod liveness;
+pub mod regalloc;
 
 use std::ops::Range;
 
-use crate::{asm::{ASM, instr::Temp}, error::ErrorTrait};
+use crate::{asm::{ASM, instr::{InstrKind, Operand, Temp}}, error::ErrorTrait, native, warning::{Lint, Warning}};
 
 use ssa::{ssa_form, Loc};
+use lint::lint;
+pub use liveness::liveness;
 
 
 pub enum SemError {
@@ -12,6 +17,11 @@ pub enum SemError {
   InvalidCFG,
   MultiDefs(Temp, Loc, Loc),
   NoDef(Temp, Loc),
+  EntryPreds(String, String, u64),
+  EntryPhi(String, Temp, u64),
+  ExtCheck(String, String, u64),
+  UnknownFunc(String, u64),
+  CallArity(String, usize, usize, u64),
 }
 
 impl ErrorTrait for SemError {
@@ -21,6 +31,11 @@ impl ErrorTrait for SemError {
       Self::InvalidCFG => 1,
       Self::MultiDefs(_, _, _) => 2,
       Self::NoDef(_, _) => 3,
+      Self::EntryPreds(_, _, _) => 4,
+      Self::EntryPhi(_, _, _) => 5,
+      Self::ExtCheck(_, _, _) => 6,
+      Self::UnknownFunc(_, _) => 7,
+      Self::CallArity(_, _, _, _) => 8,
     }
   }
 
@@ -28,6 +43,11 @@ impl ErrorTrait for SemError {
     match self {
       Self::NoMain => "Missing Function `main`",
       Self::InvalidCFG => "Invalid CFG TODO",
+      Self::EntryPreds(_, _, _) => "Entry Block Has Predecessors",
+      Self::EntryPhi(_, _, _) => "Entry Block Has a Phi Function",
+      Self::ExtCheck(_, _, _) => "Invalid Extension Instruction",
+      Self::UnknownFunc(_, _) => "Call to an Unknown Function",
+      Self::CallArity(_, _, _, _) => "Wrong Number of Arguments",
       _ => "TODO",
       // Self::MultiDefs(temp, _, _) => format!("Temporary `{}` is Defined Multiple Times", temp),
       // Self::NoDef(temp, _) => format!("Temporary `{}` is Never Defined", temp),
@@ -41,6 +61,23 @@ impl ErrorTrait for SemError {
   fn note(&self) -> Option<String> {
     match self {
       Self::NoMain => Some("C0 VM needs a function called `main` to start executing at".to_string()),
+      Self::EntryPreds(_, _, _) | Self::EntryPhi(_, _, _) =>
+        Some("execution starts at the first block of a function, so it cannot have predecessors; \
+          add a new block in front of it".to_string()),
+      Self::UnknownFunc(_, _) => Some(format!("functions that are not defined in the program must be one of the native \
+        functions: {}", native::NATIVES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", "))),
+      _ => None,
+    }
+  }
+
+  fn line_label(&self) -> Option<(String, u64)> {
+    match self {
+      Self::EntryPreds(func, bid, line) => Some((format!("`{}` is the entry block of `{}`", bid, func), *line)),
+      Self::EntryPhi(func, dest, line) => Some((format!("`{}` is defined by a phi in the entry block of `{}`", dest, func), *line)),
+      Self::ExtCheck(mnemonic, msg, line) => Some((format!("`{}`: {}", mnemonic, msg), *line)),
+      Self::UnknownFunc(name, line) => Some((format!("`{}` is not defined", name), *line)),
+      Self::CallArity(name, expected, found, line) =>
+        Some((format!("`{}` takes {} argument(s) but is given {}", name, expected, found), *line)),
       _ => None,
     }
   }
@@ -53,17 +90,81 @@ fn has_main(abs: &ASM) -> SemResult {
   if abs.contains_key("main") { Ok(()) } else { Err(SemError::NoMain) }
 }
 
+/// The entry block is the first block of each function, and must have no predecessors or phis
+fn entry_blocks(abs: &ASM) -> SemResult {
+  for func in abs.values() {
+    let entry = func.entry();
+    let block = &func.blocks[&entry];
+
+    if !block.preds.is_empty() {
+      return Err(SemError::EntryPreds(func.name.clone(), func.label(entry), block.line_start));
+    }
+
+    for instr in block.lines.iter() {
+      if let InstrKind::Phi { dest, .. } = &instr.kind {
+        return Err(SemError::EntryPhi(func.name.clone(), dest.clone(), instr.line));
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Run the semantic check of every extension instruction
+fn ext_checks(abs: &ASM) -> SemResult {
+  for func in abs.values() {
+    for instr in func.blocks.values().flat_map(|block| block.lines.iter()) {
+      if let InstrKind::Ext { ext, srcs, .. } = &instr.kind {
+        let consts: Vec<_> = srcs.iter().map(|src| match src {
+          Operand::Const(val) => Some(*val),
+          Operand::Temp(_) => None,
+        }).collect();
+
+        ext.check(&consts).map_err(|msg| SemError::ExtCheck(ext.mnemonic().to_string(), msg, instr.line))?;
+      }
+    }
+  }
+
+  Ok(())
+}
+
+/// Every call must be to a function of the program or a native function, with the right number of arguments
+fn call_targets(abs: &ASM) -> SemResult {
+  for func in abs.values() {
+    for instr in func.blocks.values().flat_map(|block| block.lines.iter()) {
+      if let InstrKind::Call { name, src, .. } = &instr.kind {
+        let arity = match abs.get(name) {
+          Some(callee) => Some(callee.params.len()),
+          None => native::arity(name).ok_or_else(|| SemError::UnknownFunc(name.clone(), instr.line))?,
+        };
+
+        match arity {
+          Some(arity) if arity != src.len() => return Err(SemError::CallArity(name.clone(), arity, src.len(), instr.line)),
+          _ => {},
+        }
+      }
+    }
+  }
+
+  Ok(())
+}
+
 
 // fn validate_cfg -> Check if the predecessors correspond with the successor construction
 // fn check_phis -> Check if number of args to phi functions is equal to num predecessors
 
-pub fn sem_analysis(abs: &ASM, ssa: bool) -> SemResult {
+pub fn sem_analysis(abs: &ASM, ssa: bool, lints: &dyn Fn(Lint) -> bool, warnings: &mut Vec<Warning>) -> SemResult {
   has_main(abs)?;
+  entry_blocks(abs)?;
+  ext_checks(abs)?;
+  call_targets(abs)?;
 
   // SSA Checks
   if ssa {
     ssa_form(abs)?;
   }
 
+  // Non-Fatal Checks
+  lint(abs, lints, warnings);
   Ok(())
 }
@@ -0,0 +1,257 @@
+use std::fmt::{Display, Formatter, Error as FmtError};
+use std::ops::Range;
+
+use fxhash::{FxHashMap, FxHashSet};
+use itertools::Itertools;
+
+use crate::asm::ASM;
+use crate::asm::blocks::Func;
+use crate::asm::instr::{InstrKind, Operand, Temp, TempID};
+use crate::error::ErrorTrait;
+use crate::exec::ReturnType;
+
+use super::liveness::liveness;
+
+
+/// Location (Register or Spill Temp) Assigned to Each Temp, per Function
+pub type AllocMap = FxHashMap<String, FxHashMap<Temp, Temp>>;
+
+/// File that an Allocation Error Points Into
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum AllocFile {
+  Pre,
+  Post,
+  Map,
+}
+
+pub enum AllocError {
+  BadMapping(u64),
+  InferFailed(String, u64, u64),
+  Unmapped(Temp, u64),
+  Conflict(Temp, Temp, Temp, u64),
+  ResultMismatch(ReturnType, ReturnType),
+  PrintMismatch(Option<(u64, i32)>, Option<(u64, i32)>),
+}
+
+impl AllocError {
+  /// File that `line_label` refers to
+  pub const fn file(&self) -> AllocFile {
+    match self {
+      Self::BadMapping(_) => AllocFile::Map,
+      Self::PrintMismatch(None, Some(_)) => AllocFile::Post,
+      _ => AllocFile::Pre,
+    }
+  }
+}
+
+impl Display for AllocError {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+    write!(f, "error[A{}]: {}", self.code(), self.message())?;
+    if let Some((label, line)) = self.line_label() {
+      write!(f, " - {} (line {})", label, line)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl ErrorTrait for AllocError {
+  fn code(&self) -> u64 {
+    match self {
+      Self::BadMapping(_) => 0,
+      Self::InferFailed(_, _, _) => 1,
+      Self::Unmapped(_, _) => 2,
+      Self::Conflict(_, _, _, _) => 3,
+      Self::ResultMismatch(_, _) => 4,
+      Self::PrintMismatch(_, _) => 5,
+    }
+  }
+
+  fn message(&self) -> &'static str {
+    match self {
+      Self::BadMapping(_) => "Invalid Line in Allocation Mapping",
+      Self::InferFailed(_, _, _) => "Unable to Infer Allocation Mapping",
+      Self::Unmapped(_, _) => "Temporary Has No Allocated Location",
+      Self::Conflict(_, _, _, _) => "Interfering Temporaries Share a Location",
+      Self::ResultMismatch(_, _) => "Programs Return Different Results",
+      Self::PrintMismatch(_, _) => "Programs Print Different Values",
+    }
+  }
+
+  fn label(&self) -> Option<(String, Range<usize>)> {
+    None
+  }
+
+  fn line_label(&self) -> Option<(String, u64)> {
+    match self {
+      Self::BadMapping(line) =>
+        Some(("expected a function name or a pair of temps `#<temp> #<location>`".to_string(), *line)),
+      Self::InferFailed(func, line, _) =>
+        Some((format!("instruction in `{}` does not match the allocated program", func), *line)),
+      Self::Unmapped(temp, line) => Some((format!("`{}` is defined here", temp), *line)),
+      Self::Conflict(temp, other, loc, line) =>
+        Some((format!("`{}` is defined in `{}` here while `{}` is still live there", temp, loc, other), *line)),
+      Self::ResultMismatch(_, _) => None,
+      Self::PrintMismatch(Some((line, val)), _) => Some((format!("prints {} before allocation", val), *line)),
+      Self::PrintMismatch(None, Some((line, val))) => Some((format!("prints {} after allocation", val), *line)),
+      Self::PrintMismatch(None, None) => None,
+    }
+  }
+
+  fn note(&self) -> Option<String> {
+    match self {
+      Self::InferFailed(_, _, post) => Some(format!(
+        "the mapping can only be inferred when both programs have the same shape; \
+        see line {} of the allocated program, or pass the mapping with `--alloc-map`", post
+      )),
+      Self::ResultMismatch(pre, post) => Some(format!("expected `{}` but the allocated program gives `{}`", pre, post)),
+      Self::PrintMismatch(_, Some((line, val))) => Some(format!("the allocated program prints {} at line {}", val, line)),
+      Self::PrintMismatch(_, None) => Some("the allocated program stops printing here".to_string()),
+      _ => None,
+    }
+  }
+}
+
+
+/// Parse a mapping file, where each function name is followed by one `#<temp> #<location>` pair per line:
+/// ```text
+/// main
+///   #0 #eax
+///   #1 #ebx
+/// ```
+pub fn parse_map(map_str: &str) -> Result<AllocMap, AllocError> {
+  let mut map = AllocMap::default();
+  let mut func: Option<String> = None;
+
+  for (idx, line) in map_str.lines().enumerate() {
+    let line_no = idx as u64 + 1;
+    let line = line.split("//").next().unwrap().trim();
+
+    match (line.split_whitespace().collect_vec().as_slice(), &func) {
+      ([], _) => {},
+      ([name], _) if !name.starts_with('#') => func = Some(name.to_string()),
+      ([temp, loc], Some(func)) => {
+        let (temp, loc) = temp.parse().and_then(|temp| loc.parse().map(|loc| (temp, loc)))
+          .map_err(|_| AllocError::BadMapping(line_no))?;
+        map.entry(func.clone()).or_default().insert(temp, loc);
+      },
+      _ => return Err(AllocError::BadMapping(line_no)),
+    }
+  }
+
+  Ok(map)
+}
+
+
+/// Assign a location to a temp, failing if it was already given a different one
+fn infer_temp(map: &mut FxHashMap<Temp, Temp>, pre: &Temp, post: &Temp) -> bool {
+  map.entry(pre.clone()).or_insert_with(|| post.clone()) == post
+}
+
+fn infer_func(pre: &Func, post: &Func) -> Result<FxHashMap<Temp, Temp>, AllocError> {
+  let fail = |pre_line, post_line| AllocError::InferFailed(pre.name.clone(), pre_line, post_line);
+  let mut map = FxHashMap::default();
+
+  if pre.params.len() != post.params.len() || pre.blocks.len() != post.blocks.len() {
+    return Err(fail(pre.line_start, post.line_start));
+  }
+
+  for (pre_param, post_param) in pre.params.iter().zip(post.params.iter()) {
+    if !infer_temp(&mut map, pre_param, post_param) {
+      return Err(fail(pre.line_start, post.line_start));
+    }
+  }
+
+  for (bid, pre_block) in pre.blocks.iter() {
+    let post_block = post.blocks.get(bid).ok_or_else(|| fail(pre_block.line_start, post.line_start))?;
+    if pre_block.lines.len() != post_block.lines.len() {
+      return Err(fail(pre_block.line_start, post_block.line_start));
+    }
+
+    for (pre_instr, post_instr) in pre_block.lines.iter().zip(post_block.lines.iter()) {
+      let same_kind = std::mem::discriminant(&pre_instr.kind) == std::mem::discriminant(&post_instr.kind);
+      let mut srcs = pre_instr.srcs().into_iter().zip_longest(post_instr.srcs());
+
+      let matches = same_kind && srcs.all(|pair| match pair.both() {
+        Some((Operand::Temp(pre_src), Operand::Temp(post_src))) => infer_temp(&mut map, pre_src, post_src),
+        Some((_, Operand::Const(_))) => true,
+        _ => false,
+      }) && match (pre_instr.dest(), post_instr.dest()) {
+        (Some(pre_dest), Some(post_dest)) => infer_temp(&mut map, pre_dest, post_dest),
+        (None, None) => true,
+        _ => false,
+      };
+
+      if !matches {
+        return Err(fail(pre_instr.line, post_instr.line));
+      }
+    }
+  }
+
+  Ok(map)
+}
+
+/// Infer the mapping by matching up the temps of two programs with the same shape
+pub fn infer_map(pre: &ASM, post: &ASM) -> Result<AllocMap, AllocError> {
+  pre.iter().map(|(name, pre_func)| {
+    let post_func = post.get(name).ok_or_else(|| AllocError::InferFailed(name.clone(), pre_func.line_start, 0))?;
+    Ok((name.clone(), infer_func(pre_func, post_func)?))
+  }).collect()
+}
+
+
+/// Check that no two temps that are live at the same time share a location
+/// A move `#a = #b` may put both temps in the same location since they hold the same value
+pub fn check_conflicts(pre: &ASM, map: &AllocMap) -> Vec<AllocError> {
+  let mut errors = vec![];
+  let empty = FxHashMap::default();
+
+  for func in pre.values() {
+    let func_map = map.get(&func.name).unwrap_or(&empty);
+    let loc = |temp: &Temp| match &temp.0 {
+      TempID::Reg(_) => Some(temp.clone()),
+      TempID::Num(_) => func_map.get(temp).cloned(),
+    };
+
+    let live = liveness(func);
+    let mut unmapped = FxHashSet::default();
+    let mut conflicts = FxHashSet::default();
+
+    // Parameters are All Live at the Start
+    let entry_live = live.live_in.get(&func.entry()).cloned().unwrap_or_default();
+    for (param, other) in func.params.iter().tuple_combinations() {
+      if entry_live.contains(param) && entry_live.contains(other) && loc(param).is_some() && loc(param) == loc(other) {
+        errors.push(AllocError::Conflict(param.clone(), other.clone(), loc(param).unwrap(), func.line_start));
+      }
+    }
+
+    live.for_each_instr(func, |_, instr, live_after| {
+      let dest = match instr.dest() {
+        Some(dest) => dest,
+        None => return,
+      };
+
+      let dest_loc = match loc(dest) {
+        Some(dest_loc) => dest_loc,
+        None => {
+          if unmapped.insert(dest.clone()) {
+            errors.push(AllocError::Unmapped(dest.clone(), instr.line));
+          }
+          return;
+        },
+      };
+
+      for other in live_after.iter().sorted() {
+        let is_move_src = matches!(&instr.kind, InstrKind::Mov { src: Operand::Temp(src), .. } if src == other);
+        if other != dest && !is_move_src && loc(other).as_ref() == Some(&dest_loc)
+          && conflicts.insert((dest.clone(), other.clone()))
+        {
+          errors.push(AllocError::Conflict(dest.clone(), other.clone(), dest_loc.clone(), instr.line));
+        }
+      }
+    });
+  }
+
+  errors.sort_by_key(|err| err.line_label().map(|(_, line)| line));
+  errors
+}
@@ -2,6 +2,9 @@ use std::path::PathBuf;
 
 use structopt::StructOpt;
 
+use crate::ext::Extensions;
+use crate::warning::{Level, Lint, LintName};
+
 
 /// Configuration options for VM
 #[derive(Debug, StructOpt)]
@@ -15,21 +18,84 @@ pub struct Config {
   #[structopt(long="ssa")]
   pub ssa: bool,
 
+  /// Print the Live-In and Live-Out Sets of Each Block Instead of Running
+  #[structopt(long="liveness")]
+  pub liveness: bool,
+
+  /// Validate FILE as the Register Allocation of the Pre-Allocation Program PRE
+  #[structopt(long="regalloc", name="PRE", parse(from_os_str))]
+  pub regalloc: Option<PathBuf>,
+
+  /// Mapping from Temps of PRE to Their Allocated Locations (Inferred if Missing)
+  #[structopt(long="alloc-map", name="MAP", parse(from_os_str), requires="PRE")]
+  pub alloc_map: Option<PathBuf>,
+
+  /// Run FILE and OTHER and Report the First Place they Behave Differently
+  #[structopt(long="diff", name="OTHER", parse(from_os_str))]
+  pub diff: Option<PathBuf>,
+
+  /// Write the CFG of Each Function as `<DIR>/<function>.dot` Instead of Running
+  #[structopt(long="dot", name="DIR", parse(from_os_str))]
+  pub dot: Option<PathBuf>,
+
+  /// Run the Program First to Color the CFG by How Often Each Block Executes
+  #[structopt(long="dot-counts", requires="DIR")]
+  pub dot_counts: bool,
+
+  /// Feed INPUT to the Program Instead of Stdin
+  #[structopt(long="input", name="INPUT", parse(from_os_str))]
+  pub input: Option<PathBuf>,
+
   /// Enable Strict SSA Mode
   #[structopt(long="timeout")]
   pub timeout: Option<u64>,
 
+  /// Silence a Warning (`warnings` for All)
+  #[structopt(short="A", long="allow", number_of_values=1)]
+  pub allow: Vec<LintName>,
+
+  /// Report a Warning (`warnings` for All)
+  #[structopt(short="W", long="warn", number_of_values=1)]
+  pub warn: Vec<LintName>,
+
+  /// Promote a Warning to an Error (`warnings` for All)
+  #[structopt(short="D", long="deny", number_of_values=1)]
+  pub deny: Vec<LintName>,
+
   /// Input Directory of Test Cases
   #[structopt(name = "FILE", parse(from_os_str))]
   pub file_name: PathBuf,
+
+  /// Extension Instructions Registered by a Downstream Crate
+  #[structopt(skip)]
+  pub extensions: Extensions,
 }
 
 impl Config {
   pub fn new_defaults(file_name: PathBuf) -> Self {
-    Self { file_name, ssa: false, verbose: false, timeout: None }
+    Self {
+      file_name, ssa: false, verbose: false, liveness: false, timeout: None,
+      regalloc: None, alloc_map: None, diff: None, dot: None, dot_counts: false, input: None,
+      allow: vec![], warn: vec![], deny: vec![], extensions: Extensions::default(),
+    }
   }
 
   pub fn new_timeout(file_name: PathBuf, timeout: u64) -> Self {
-    Self { file_name, ssa: false, verbose: false, timeout: Some(timeout) }
+    Self { timeout: Some(timeout), ..Self::new_defaults(file_name) }
+  }
+
+  /// Level of a lint after applying the `-A`, `-W` and `-D` flags
+  /// Naming a lint directly beats `warnings`, and ties go to the stricter level
+  /// Lints that are allowed by default are only enabled by naming them
+  pub fn lint_level(&self, lint: Lint) -> Level {
+    let named = |name: LintName| [
+      (&self.deny, Level::Deny),
+      (&self.warn, Level::Warn),
+      (&self.allow, Level::Allow),
+    ].into_iter().find(|(names, _)| names.contains(&name)).map(|(_, level)| level);
+
+    named(LintName::One(lint))
+      .or_else(|| if lint.default_level() == Level::Allow { None } else { named(LintName::All) })
+      .unwrap_or(lint.default_level())
   }
 }
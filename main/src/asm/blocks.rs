@@ -1,14 +1,15 @@
 use std::fmt::{Display, Error, Formatter};
 
 use derives::DebugFromDisplay;
-use fxhash::FxHashMap;
+use fxhash::{FxBuildHasher, FxHashMap};
+use indexmap::IndexMap;
 use itertools::Itertools;
 
 use crate::ops::BinOp;
-use super::instr::{Operand, Instr, Temp};
+use super::instr::{Operand, Instr, InstrKind, SrcLoc, Temp};
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Cond {
   BinOp(Operand, BinOp, Operand),
   Value(Operand),
@@ -27,6 +28,11 @@ impl Display for Cond {
 #[derive(DebugFromDisplay, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct BlockID(pub u64);
 
+impl BlockID {
+  /// Named labels like `@loop_head` are given IDs from here on up, in the order they first appear
+  pub const LABEL_BASE: u64 = 1 << 63;
+}
+
 impl Display for BlockID {
   fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
     write!(f, "@{}", self.0)
@@ -34,13 +40,34 @@ impl Display for BlockID {
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BranchKind {
   Cond(Cond, BlockID, BlockID),
   Jump(BlockID),
   Ret(Option<Operand>),
 }
 
+impl BranchKind {
+  /// Operands read by the branch
+  pub fn srcs(&self) -> Vec<&Operand> {
+    match self {
+      Self::Cond(Cond::BinOp(lop, _, rop), _, _) => vec![lop, rop],
+      Self::Cond(Cond::Value(op), _, _) => vec![op],
+      Self::Jump(_) | Self::Ret(None) => vec![],
+      Self::Ret(Some(op)) => vec![op],
+    }
+  }
+
+  /// Blocks that the branch can transfer control to
+  pub fn targets(&self) -> Vec<BlockID> {
+    match self {
+      Self::Cond(_, true_block, false_block) => vec![*true_block, *false_block],
+      Self::Jump(block) => vec![*block],
+      Self::Ret(_) => vec![],
+    }
+  }
+}
+
 impl Display for BranchKind {
   fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
     match self {
@@ -57,6 +84,15 @@ impl Display for BranchKind {
 pub struct Branch {
   pub kind: BranchKind,
   pub line: u64,
+  pub comments: Vec<String>,
+  pub loc: Option<SrcLoc>,
+}
+
+/// Compared like `Func`
+impl PartialEq for Branch {
+  fn eq(&self, other: &Self) -> bool {
+    self.kind == other.kind && self.loc == other.loc
+  }
 }
 
 impl Display for Branch {
@@ -73,6 +109,29 @@ pub struct BasicBlock {
   pub lines: Vec<Instr>,
   pub branch: Branch,
   pub line_start: u64,
+  pub comments: Vec<String>,
+}
+
+impl BasicBlock {
+  /// Successor blocks, including side-exits taken by `if` instructions
+  pub fn succs(&self) -> Vec<BlockID> {
+    let mut succs: Vec<_> = self.lines.iter()
+      .filter_map(|instr| match &instr.kind {
+        InstrKind::If { block, .. } => Some(*block),
+        _ => None,
+      })
+      .collect();
+
+    succs.extend(self.branch.kind.targets());
+    succs
+  }
+}
+
+/// Compared like `Func`
+impl PartialEq for BasicBlock {
+  fn eq(&self, other: &Self) -> bool {
+    self.id == other.id && self.preds == other.preds && self.lines == other.lines && self.branch == other.branch
+  }
 }
 
 impl Display for BasicBlock {
@@ -88,12 +147,41 @@ impl Display for BasicBlock {
 }
 
 
+/// Blocks of a Function, in Source Order
+pub type Blocks = IndexMap<BlockID, BasicBlock, FxBuildHasher>;
+
+#[derive(Clone)]
 pub struct Func {
   pub name: String,
   pub params: Vec<Temp>,
-  pub blocks: FxHashMap<BlockID, BasicBlock>,
+  pub blocks: Blocks,
   pub line_start: u64,
   pub count: Option<u64>,
+  pub comments: Vec<String>,
+  /// Original names of the blocks written as named labels
+  pub labels: FxHashMap<BlockID, String>,
+}
+
+impl Func {
+  /// The block that execution of the function starts at, which is the first one in the source
+  pub fn entry(&self) -> BlockID {
+    *self.blocks.keys().next().unwrap()
+  }
+
+  /// Block as written in the source, either `@<uint>` or its named label
+  pub fn label(&self, bid: BlockID) -> String {
+    match self.labels.get(&bid) {
+      Some(label) => format!("@{}", label),
+      None => bid.to_string(),
+    }
+  }
+}
+
+/// Line numbers, temp counts and comments are ignored so that a reformatted function is equal to the original
+impl PartialEq for Func {
+  fn eq(&self, other: &Self) -> bool {
+    self.name == other.name && self.params == other.params && self.blocks == other.blocks && self.labels == other.labels
+  }
 }
 
 impl Display for Func {
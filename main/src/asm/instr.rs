@@ -1,8 +1,10 @@
 use std::fmt::{Display, Error, Formatter};
+use std::str::FromStr;
 
 use derives::DebugFromDisplay;
 use itertools::Itertools;
 
+use crate::ext::Ext;
 use crate::ops::{BinOp, UnOp};
 
 use super::{blocks::BlockID, reg::Register};
@@ -32,11 +34,43 @@ impl Display for Temp {
   }
 }
 
+impl FromStr for Temp {
+  type Err = ();
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let name = s.strip_prefix('#').ok_or(())?;
+
+    Register::from_str(name).map(TempID::Reg)
+      .or_else(|_| name.parse().map(TempID::Num).map_err(|_| ()))
+      .map(Temp)
+  }
+}
+
+
+/// Location in the Original C0 Source, Given by a `.loc file:line:col` Directive
+#[derive(DebugFromDisplay, Clone, PartialEq, Eq)]
+pub struct SrcLoc {
+  pub file: String,
+  pub line: u64,
+  pub col: Option<u64>,
+}
+
+impl Display for SrcLoc {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+    write!(f, "{}:{}", self.file, self.line)?;
+    if let Some(col) = self.col {
+      write!(f, ":{}", col)?;
+    }
+
+    Ok(())
+  }
+}
+
 
 // TODO: Consider adding types to operands or instructions
 // Would be useful for Lab 4 if we plan on supporting it
 // Can use an argument to disable for previous labs, which would set all to i32
-#[derive(DebugFromDisplay, Clone)]
+#[derive(DebugFromDisplay, Clone, PartialEq, Eq)]
 pub enum Operand {
   Temp(Temp),
   Const(i32),
@@ -52,7 +86,7 @@ impl Display for Operand {
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum InstrKind {
   BinOp {
     op: BinOp,
@@ -93,7 +127,14 @@ pub enum InstrKind {
   },
 
   Dump,
-  Nop
+  Nop,
+
+  /// Instruction registered through `ext::Extensions`
+  Ext {
+    ext: Ext,
+    dest: Option<Temp>,
+    srcs: Vec<Operand>,
+  },
 }
 
 impl Display for InstrKind {
@@ -112,6 +153,9 @@ impl Display for InstrKind {
       Self::Print { value, .. } => write!(f, "print {}", value),
       Self::Dump => write!(f, "dump"),
       Self::Nop => write!(f, "nop"),
+      Self::Ext { ext, dest: Some(dest), srcs } =>
+        write!(f, "{} = {} {}", dest, ext.mnemonic(), srcs.iter().format(" ")),
+      Self::Ext { ext, dest: None, srcs } => write!(f, "{} {}", ext.mnemonic(), srcs.iter().format(" ")),
     }
   }
 }
@@ -120,6 +164,10 @@ impl Display for InstrKind {
 pub struct Instr {
   pub line: u64,
   pub kind: InstrKind,
+  /// Text of the comments attached to the instruction, without the leading `//`
+  pub comments: Vec<String>,
+  /// Source location from the closest `.loc` directive above the instruction
+  pub loc: Option<SrcLoc>,
 }
 
 impl Instr {
@@ -129,10 +177,34 @@ impl Instr {
         | InstrKind::UnOp { dest, .. }
         | InstrKind::Mov { dest, .. }
         | InstrKind::Phi { dest, .. }
-        | InstrKind::Call { dest: Some(dest), .. } => Some(dest),
+        | InstrKind::Call { dest: Some(dest), .. }
+        | InstrKind::Ext { dest: Some(dest), .. } => Some(dest),
       _ => None
     }
   }
+
+  /// Operands read by the instruction
+  /// Note that the sources of a phi are only read along their incoming edge
+  pub fn srcs(&self) -> Vec<&Operand> {
+    match &self.kind {
+      InstrKind::BinOp { src1, src2, .. } => vec![src1, src2],
+      InstrKind::UnOp { src, .. }
+        | InstrKind::Mov { src, .. } => vec![src],
+      InstrKind::If { cond, .. } => vec![cond],
+      InstrKind::Phi { srcs, .. } => srcs.iter().collect(),
+      InstrKind::Call { src, .. } => src.iter().collect(),
+      InstrKind::Ext { srcs, .. } => srcs.iter().collect(),
+      InstrKind::Print { value } => vec![value],
+      InstrKind::Dump | InstrKind::Nop => vec![],
+    }
+  }
+}
+
+/// Compared like `Func`
+impl PartialEq for Instr {
+  fn eq(&self, other: &Self) -> bool {
+    self.kind == other.kind && self.loc == other.loc
+  }
 }
 
 impl Display for Instr {
@@ -3,9 +3,11 @@ pub mod blocks;
 pub mod reg;
 
 
-use fxhash::FxHashMap;
+use fxhash::FxBuildHasher;
+use indexmap::IndexMap;
 
 use blocks::Func;
 
 
-pub type ASM = FxHashMap<String, Func>;
+/// Functions of the Program, in Source Order
+pub type ASM = IndexMap<String, Func, FxBuildHasher>;
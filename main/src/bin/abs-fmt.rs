@@ -0,0 +1,94 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use structopt::StructOpt;
+
+use vm::Radix;
+
+
+/// Configuration options for the Formatter
+#[derive(Debug, StructOpt)]
+#[structopt(name="abs-fmt", about="Formatter for Abstract Assembly Files", no_version)]
+struct Options {
+  /// Only Check that the Files are Formatted, Listing the Ones that are Not
+  #[structopt(long="check")]
+  check: bool,
+
+  /// Spell Constants in Hexadecimal Instead of Decimal
+  #[structopt(long="hex")]
+  hex: bool,
+
+  /// Files to Format in Place (Formats stdin to stdout if None are Given)
+  #[structopt(name="FILES", parse(from_os_str))]
+  files: Vec<PathBuf>,
+}
+
+/// Formatted Version of the Source, or the Parse Error as a Message
+fn format(source: &str, radix: Radix) -> Result<String, String> {
+  vm::parse(source)
+    .map(|program| program.format(radix))
+    .map_err(|err| err.to_string())
+}
+
+/// Format a Single File, Returning Whether it Was Already Formatted
+fn format_file(options: &Options, path: &Path, radix: Radix) -> Result<bool, String> {
+  let source = fs::read_to_string(path)
+    .map_err(|err| format!("\x1b[1m\x1b[91merror[F02]\x1b[0m\x1b[1m: Unable to Read File `{}`\x1b[0m\n{}", path.display(), err))?;
+  let formatted = format(&source, radix).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+  if formatted == source {
+    return Ok(true);
+  }
+
+  if options.check {
+    println!("{}", path.display());
+  } else {
+    fs::write(path, formatted)
+      .map_err(|err| format!("\x1b[1m\x1b[91merror[F04]\x1b[0m\x1b[1m: Unable to Write File `{}`\x1b[0m\n{}", path.display(), err))?;
+  }
+
+  Ok(false)
+}
+
+
+fn main() {
+  let options = Options::from_args();
+  let radix = if options.hex { Radix::Hex } else { Radix::Dec };
+
+  // Format stdin to stdout
+  if options.files.is_empty() {
+    let mut source = String::new();
+    if let Err(err) = io::stdin().read_to_string(&mut source) {
+      eprintln!("\x1b[1m\x1b[91merror[F02]\x1b[0m\x1b[1m: Unable to Read stdin\x1b[0m\n{}", err);
+      process::exit(1);
+    }
+
+    match format(&source, radix) {
+      Ok(formatted) if options.check => process::exit(if formatted == source { 0 } else { 1 }),
+      Ok(formatted) => print!("{}", formatted),
+      Err(err) => {
+        eprintln!("<stdin>: {}", err);
+        process::exit(1);
+      },
+    }
+    return;
+  }
+
+  // With `--check`, Fail if Any File Would Change
+  let mut failed = false;
+  for path in options.files.iter() {
+    match format_file(&options, path, radix) {
+      Ok(formatted) => failed |= options.check && !formatted,
+      Err(err) => {
+        eprintln!("{}", err);
+        failed = true;
+      },
+    }
+  }
+
+  if failed {
+    process::exit(1);
+  }
+}
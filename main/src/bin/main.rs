@@ -1,27 +1,71 @@
 use std::fs;
-use std::io::{BufReader, Read};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops::Range;
+use std::path::Path;
+use std::process;
 
 use structopt::StructOpt;
-use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFiles;
+use codespan_reporting::diagnostic::{Diagnostic, Label, LabelStyle, Severity};
+use codespan_reporting::files::{Files, SimpleFiles};
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use codespan_reporting::term;
 
 use vm::ErrorTrait;
-use vm::{self, args::Config, ReturnType};
+use vm::{self, args::Config, AllocFile, Outcome, Program, ReturnType, TraceEvent};
 
 
-fn main() {
-  let config = Config::from_args();
-  let file_name = config.file_name.as_path();
+type SourceFiles = SimpleFiles<String, String>;
+
+/// Range of the Text on a Line, Ignoring Surrounding Whitespace
+fn line_range(files: &SourceFiles, file_id: usize, line: u64) -> Option<Range<usize>> {
+  let range = files.line_range(file_id, line.checked_sub(1)? as usize).ok()?;
+  let text = &files.source(file_id).ok()?[range.clone()];
+  let start = range.start + (text.len() - text.trim_start().len());
+  let end = (range.start + text.trim_end().len()).max(start);
+  Some(start..end)
+}
+
+/// Build a Diagnostic for any Error or Warning in the File
+fn diagnostic<E: ErrorTrait>(
+  err: &E, severity: Severity, code: String, files: &SourceFiles, file_id: usize
+) -> Diagnostic<usize> {
+  let mut diagnostic = Diagnostic::new(severity)
+    .with_message(err.message())
+    .with_code(code);
+
+  if let Some((label, range)) = err.label() {
+    diagnostic = diagnostic.with_labels(vec![
+      Label::primary(file_id, range).with_message(label)
+    ]);
+  } else if let Some((label, line)) = err.line_label() {
+    if let Some(range) = line_range(files, file_id, line) {
+      diagnostic = diagnostic.with_labels(vec![
+        Label::primary(file_id, range).with_message(label)
+      ]);
+    }
+  }
+
+  if let Some(note) = err.note() {
+    diagnostic = diagnostic.with_notes(vec![note]);
+  }
+
+  diagnostic
+}
+
+fn emit(files: &SourceFiles, diagnostic: &Diagnostic<usize>) {
+  let writer = StandardStream::stderr(ColorChoice::Always);
+  term::emit(&mut writer.lock(), &term::Config::default(), files, diagnostic).unwrap();
+}
 
+/// Read a File and Add it to the Source Files
+fn read_file(files: &mut SourceFiles, file_name: &Path) -> Option<usize> {
   // Determine if File Exists
   let mut file = BufReader::new(
-    if let Ok(file) = fs::File::open(&file_name) {
+    if let Ok(file) = fs::File::open(file_name) {
       file
     } else {
       eprintln!("\x1b[1m\x1b[91merror[F01]\x1b[0m\x1b[1m: File `{}` Not Found\x1b[0m", file_name.display());
-      return;
+      return None;
     }
   );
 
@@ -30,35 +74,229 @@ fn main() {
   if let Err(err) = file.read_to_string(&mut file_str) {
     eprintln!("\x1b[1m\x1b[91merror[F02]\x1b[0m\x1b[1m: Unable to Read File `{}`\x1b[0m\n{}", file_name.display(), err);
   }
-  
-  let mut files = SimpleFiles::new();
-  let file_id = files.add(
-    file_name.file_name().unwrap().to_string_lossy(), 
-    &file_str,
-  );
 
-  match vm::run(&config, file_str.as_str()) {
-    Ok(ReturnType::Return(val)) => println!("return {}", val),
-    Ok(ReturnType::DivByZero) => println!("div-by-zero"),
-    Ok(ReturnType::Timeout) => println!("timeout"),
+  Some(files.add(file_name.file_name().unwrap().to_string_lossy().to_string(), file_str))
+}
 
+/// Report Warnings and the Error (if Any) of a Stage
+fn report<T>(files: &SourceFiles, file_id: usize, warnings: Vec<vm::warning::Warning>, res: Result<T, vm::Error>) -> Option<T> {
+  for warning in warnings.iter() {
+    emit(files, &diagnostic(warning, Severity::Warning, format!("W{}", warning.code()), files, file_id));
+  }
+
+  match res {
+    Ok(val) => Some(val),
     Err(err) => {
-      let mut diagnostic = Diagnostic::error()
-        .with_message(err.message())
-        .with_code(format!("{}{}", err.tag(), err.code()));
-
-      if let Some((label, range)) = err.label() {
-        diagnostic = diagnostic.with_labels(vec![
-          Label::primary(file_id, range).with_message(label)
-        ]);
-      }
-        
-      if let Some(note) = err.note() {
-        diagnostic = diagnostic.with_notes(vec![note]);
-      }
-        
-      let writer = StandardStream::stderr(ColorChoice::Always);
-      term::emit(&mut writer.lock(), &term::Config::default(), &files, &diagnostic).unwrap();
+      emit(files, &diagnostic(&err, Severity::Error, format!("{}{}", err.tag(), err.code()), files, file_id));
+      None
     },
   }
 }
+
+/// Parse and Check a File, Reporting Any Warnings and Errors
+fn load(config: &Config, files: &SourceFiles, file_id: usize) -> Option<Program> {
+  let mut warnings = vec![];
+  let res = vm::check(config, files.source(file_id).unwrap(), &mut warnings);
+  report(files, file_id, warnings, res)
+}
+
+
+/// Validate the Register Allocation of `pre` against the Main File
+fn regalloc(config: &Config, files: &mut SourceFiles, post_id: usize, pre_path: &Path, input: &str) -> Option<bool> {
+  let pre_id = read_file(files, pre_path)?;
+  let map_id = match &config.alloc_map {
+    Some(map_path) => Some(read_file(files, map_path)?),
+    None => None,
+  };
+
+  let pre = load(config, files, pre_id)?;
+  let post = load(config, files, post_id)?;
+  let map_str = map_id.map(|map_id| files.source(map_id).unwrap());
+
+  let errors = vm::validate_regalloc(config, &pre, &post, map_str, input);
+  for err in errors.iter() {
+    let file_id = match err.file() {
+      AllocFile::Pre => pre_id,
+      AllocFile::Post => post_id,
+      AllocFile::Map => map_id.unwrap(),
+    };
+
+    emit(files, &diagnostic(err, Severity::Error, format!("A{}", err.code()), files, file_id));
+  }
+
+  if errors.is_empty() {
+    println!("register allocation is valid");
+  }
+
+  Some(errors.is_empty())
+}
+
+
+/// Run the Main File and `other` and Report Where they Diverge
+fn diff(config: &Config, files: &mut SourceFiles, left_id: usize, right_path: &Path, input: &str) -> Option<bool> {
+  let right_id = read_file(files, right_path)?;
+  let left = load(config, files, left_id)?;
+  let right = load(config, files, right_id)?;
+
+  let diff = vm::diff(config, &left, &right, input);
+  let (left_ret, right_ret) = &diff.rets;
+  if left_ret == right_ret {
+    println!("same result: {}", left_ret);
+  } else {
+    println!("different results: {} vs {}", left_ret, right_ret);
+  }
+
+  if let Some((left_event, right_event)) = &diff.divergence {
+    let mut labels = vec![];
+    let mut notes = vec![];
+
+    for (event, file_id, style) in [(left_event, left_id, LabelStyle::Primary), (right_event, right_id, LabelStyle::Secondary)] {
+      match event {
+        Some(TraceEvent { event, line, comments }) => {
+          match line_range(files, file_id, *line) {
+            Some(range) => labels.push(Label::new(style, file_id, range).with_message(event.to_string())),
+            None => notes.push(format!("`{}` does {} at line {}", files.name(file_id).unwrap(), event, line)),
+          }
+
+          for comment in comments {
+            notes.push(format!("`{}` line {}: //{}", files.name(file_id).unwrap(), line, comment));
+          }
+        },
+        None => notes.push(format!("`{}` has no more events", files.name(file_id).unwrap())),
+      }
+    }
+
+    emit(files, &Diagnostic::error()
+      .with_message("Programs Diverge")
+      .with_code("D0")
+      .with_labels(labels)
+      .with_notes(notes));
+  }
+
+  Some(diff.is_same())
+}
+
+
+/// Write the CFG of Each Function to `dir`
+fn dot(config: &Config, files: &SourceFiles, file_id: usize, dir: &Path, input: &str) -> Option<()> {
+  let program = load(config, files, file_id)?;
+
+  if let Err(err) = fs::create_dir_all(dir) {
+    eprintln!("\x1b[1m\x1b[91merror[F03]\x1b[0m\x1b[1m: Unable to Create Directory `{}`\x1b[0m\n{}", dir.display(), err);
+    return None;
+  }
+
+  for (name, graph) in vm::cfg_dot(config, &program, input) {
+    let path = dir.join(format!("{}.dot", name));
+    if let Err(err) = fs::write(&path, graph) {
+      eprintln!("\x1b[1m\x1b[91merror[F04]\x1b[0m\x1b[1m: Unable to Write File `{}`\x1b[0m\n{}", path.display(), err);
+      return None;
+    }
+
+    println!("{}", path.display());
+  }
+
+  Some(())
+}
+
+
+/// Point at the Instruction that Stopped the Program, with its Comments as Notes
+fn runtime_error(files: &SourceFiles, file_id: usize, outcome: &Outcome) {
+  let site = match &outcome.site {
+    Some(site) => site,
+    None => return,
+  };
+
+  let (code, label) = match outcome.ret {
+    ReturnType::DivByZero => ("R0", "division by zero"),
+    ReturnType::Timeout => ("R1", "still running here when the time ran out"),
+    ReturnType::Abort => ("R2", "aborted reading input here"),
+    ReturnType::Return(_) => return,
+  };
+
+  let mut notes = vec![];
+  if let Some(loc) = &site.loc {
+    notes.push(format!("in the C0 source at {}", loc));
+  }
+  notes.extend(site.comments.iter().map(|comment| format!("//{}", comment)));
+
+  let mut diagnostic = Diagnostic::error()
+    .with_message(format!("Program Stopped with {}", outcome.ret))
+    .with_code(code)
+    .with_notes(notes);
+
+  if let Some(range) = line_range(files, file_id, site.line) {
+    diagnostic = diagnostic.with_labels(vec![Label::primary(file_id, range).with_message(label)]);
+  }
+
+  emit(files, &diagnostic);
+}
+
+
+fn main() {
+  let config = Config::from_args();
+  let mut files = SimpleFiles::new();
+  let file_id = match read_file(&mut files, config.file_name.as_path()) {
+    Some(file_id) => file_id,
+    None => return,
+  };
+
+  // Programs Run More than Once Get the Same Input Each Time, and None Without `--input`
+  let input_str = || match &config.input {
+    Some(path) => fs::read_to_string(path).unwrap_or_else(|_| {
+      eprintln!("\x1b[1m\x1b[91merror[F01]\x1b[0m\x1b[1m: File `{}` Not Found\x1b[0m", path.display());
+      process::exit(1);
+    }),
+    None => String::new(),
+  };
+
+  if let Some(pre_path) = &config.regalloc {
+    if regalloc(&config, &mut files, file_id, pre_path, &input_str()) != Some(true) {
+      process::exit(1);
+    }
+    return;
+  }
+
+  if let Some(other_path) = &config.diff {
+    if diff(&config, &mut files, file_id, other_path, &input_str()) != Some(true) {
+      process::exit(1);
+    }
+    return;
+  }
+
+  if let Some(dir) = &config.dot {
+    if dot(&config, &files, file_id, dir, &input_str()).is_none() {
+      process::exit(1);
+    }
+    return;
+  }
+
+  let file_str = files.source(file_id).unwrap();
+  let mut warnings = vec![];
+
+  if config.liveness {
+    let res = vm::liveness_report(&config, file_str, &mut warnings);
+    if let Some(report) = report(&files, file_id, warnings, res) {
+      print!("{}", report);
+    }
+  } else {
+    let input: Box<dyn BufRead> = match &config.input {
+      Some(path) => match fs::File::open(path) {
+        Ok(file) => Box::new(BufReader::new(file)),
+        Err(_) => {
+          eprintln!("\x1b[1m\x1b[91merror[F01]\x1b[0m\x1b[1m: File `{}` Not Found\x1b[0m", path.display());
+          process::exit(1);
+        },
+      },
+      None => Box::new(BufReader::new(io::stdin())),
+    };
+
+    let res = vm::run_with_warnings(&config, file_str, input, &mut warnings);
+    if let Some(outcome) = report(&files, file_id, warnings, res) {
+      print!("{}", outcome.output);
+      let _ = io::stdout().flush();
+      runtime_error(&files, file_id, &outcome);
+      println!("{}", outcome.ret);
+    }
+  }
+}
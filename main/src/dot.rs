@@ -0,0 +1,85 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::asm::blocks::{BasicBlock, BranchKind, Func};
+use crate::asm::instr::InstrKind;
+use crate::exec::BlockCounts;
+use crate::printer::Printer;
+
+
+/// Escape Text for a Quoted DOT String
+fn escape(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Node Label with the Block Name and Each Instruction on its Own Line
+fn node_label(func: &Func, block: &BasicBlock, count: Option<u64>) -> String {
+  let mut label = match count {
+    Some(count) => format!("{} (x{})\\l", escape(&func.label(block.id)), count),
+    None => format!("{}\\l", escape(&func.label(block.id))),
+  };
+
+  for instr in block.lines.iter() {
+    let mut line = String::new();
+    Printer::new(&mut line).instr(func, instr).unwrap();
+    write!(label, "{}\\l", escape(&line)).unwrap();
+  }
+
+  let mut line = String::new();
+  Printer::new(&mut line).branch(func, &block.branch.kind).unwrap();
+  write!(label, "{}\\l", escape(&line)).unwrap();
+  label
+}
+
+/// Fill Color of a Block, Redder the More Often it Ran
+fn fill_color(count: u64, max: u64) -> String {
+  if count == 0 {
+    "#dddddd".to_string()
+  } else {
+    format!("0.000 {:.3} 1.000", count as f64 / max.max(1) as f64)
+  }
+}
+
+
+/// Graphviz DOT graph of the CFG of a function
+/// When `counts` is given, blocks are colored by how often they ran
+pub fn func_dot(func: &Func, counts: Option<&BlockCounts>) -> String {
+  let count = |block: &BasicBlock| counts
+    .map(|counts| counts.get(&(func.name.clone(), block.id)).copied().unwrap_or(0));
+  let max = func.blocks.values().filter_map(count).max().unwrap_or(0);
+
+  let mut out = String::new();
+  writeln!(out, "digraph \"{}\" {{", escape(&func.name)).unwrap();
+  writeln!(out, "  node [shape=box, fontname=\"monospace\"];").unwrap();
+
+  let name = |bid| escape(&func.label(bid));
+  let blocks = func.blocks.values().collect_vec();
+  for block in blocks.iter() {
+    write!(out, "  \"{}\" [label=\"{}\"", name(block.id), node_label(func, block, count(block))).unwrap();
+    if let Some(count) = count(block) {
+      write!(out, ", style=filled, fillcolor=\"{}\"", fill_color(count, max)).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+  }
+
+  for block in blocks.iter() {
+    for instr in block.lines.iter() {
+      if let InstrKind::If { block: target, .. } = &instr.kind {
+        writeln!(out, "  \"{}\" -> \"{}\" [label=\"true\", style=dashed];", name(block.id), name(*target)).unwrap();
+      }
+    }
+
+    match &block.branch.kind {
+      BranchKind::Cond(_, true_block, false_block) => {
+        writeln!(out, "  \"{}\" -> \"{}\" [label=\"true\"];", name(block.id), name(*true_block)).unwrap();
+        writeln!(out, "  \"{}\" -> \"{}\" [label=\"false\"];", name(block.id), name(*false_block)).unwrap();
+      },
+      BranchKind::Jump(target) => writeln!(out, "  \"{}\" -> \"{}\";", name(block.id), name(*target)).unwrap(),
+      BranchKind::Ret(_) => {},
+    }
+  }
+
+  writeln!(out, "}}").unwrap();
+  out
+}
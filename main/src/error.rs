@@ -3,6 +3,7 @@ use std::ops::Range;
 
 use crate::parser::error::ParseError;
 use crate::analysis::SemError;
+use crate::warning::Warning;
 
 
 pub trait ErrorTrait {
@@ -10,11 +11,18 @@ pub trait ErrorTrait {
   fn message(&self) -> &'static str;
   fn label(&self) -> Option<(String, Range<usize>)>;
   fn note(&self) -> Option<String>;
+
+  /// Label attached to a whole line of the file, for errors that
+  /// only know which line they came from
+  fn line_label(&self) -> Option<(String, u64)> {
+    None
+  }
 }
 
 pub enum Error {
   ParseError(ParseError),
-  SemError(SemError)
+  SemError(SemError),
+  Lint(Warning),
 }
 
 impl Error {
@@ -22,6 +30,7 @@ impl Error {
     match self {
       Self::ParseError(_) => "P",
       Self::SemError(_) => "S",
+      Self::Lint(_) => "W",
     }
   }
 }
@@ -31,6 +40,8 @@ impl Display for Error {
     write!(f, "error[{}{}]: {}", self.tag(), self.code(), self.message())?;
     if let Some((label, _)) = self.label() {
       write!(f, " - {}", label)?;
+    } else if let Some((label, line)) = self.line_label() {
+      write!(f, " - {} (line {})", label, line)?;
     }
 
     Ok(())
@@ -42,6 +53,7 @@ impl ErrorTrait for Error {
     match self {
       Self::ParseError(e) => e.code(),
       Self::SemError(e) => e.code(),
+      Self::Lint(e) => e.code(),
     }
   }
 
@@ -49,6 +61,7 @@ impl ErrorTrait for Error {
     match self {
       Self::ParseError(e) => e.message(),
       Self::SemError(e) => e.message(),
+      Self::Lint(e) => e.message(),
     }
   }
 
@@ -56,6 +69,7 @@ impl ErrorTrait for Error {
     match self {
       Self::ParseError(e) => e.label(),
       Self::SemError(e) => e.label(),
+      Self::Lint(e) => e.label(),
     }
   }
 
@@ -63,6 +77,15 @@ impl ErrorTrait for Error {
     match self {
       Self::ParseError(e) => e.note(),
       Self::SemError(e) => e.note(),
+      Self::Lint(e) => e.note(),
+    }
+  }
+
+  fn line_label(&self) -> Option<(String, u64)> {
+    match self {
+      Self::ParseError(e) => e.line_label(),
+      Self::SemError(e) => e.line_label(),
+      Self::Lint(e) => e.line_label(),
     }
   }
 }
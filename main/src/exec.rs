@@ -1,3 +1,6 @@
+use std::cell::RefCell;
+use std::fmt::{Display, Error, Formatter};
+use std::io::{BufRead, Cursor};
 use std::time::Instant;
 
 use chrono::Local;
@@ -5,9 +8,10 @@ use fxhash::FxHashMap;
 use itertools::Itertools;
 
 use crate::asm::ASM;
-use crate::asm::blocks::{Func, BasicBlock, BranchKind, Cond};
-use crate::asm::instr::{Temp, Operand, InstrKind, TempID};
+use crate::asm::blocks::{Func, BasicBlock, BlockID, BranchKind, Cond};
+use crate::asm::instr::{Temp, Operand, InstrKind, SrcLoc, TempID};
 use crate::asm::reg::Register;
+use crate::native;
 
 
 struct TempStore {
@@ -65,37 +69,145 @@ impl TempStore {
 pub enum ReturnType {
   Return(i32),
   DivByZero,
-  // Abort,
+  Abort,
   // MemError,
   Timeout,
 }
 
+impl Display for ReturnType {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+    match self {
+      Self::Return(val) => write!(f, "return {}", val),
+      Self::DivByZero => write!(f, "div-by-zero"),
+      Self::Abort => write!(f, "abort"),
+      Self::Timeout => write!(f, "timeout"),
+    }
+  }
+}
+
+/// Observable Effects of a Program, used to Compare Two Executions
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum Event {
+  Print(i32),
+  Call(String, Vec<i32>),
+  Return(String, i32),
+}
+
+impl Display for Event {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+    match self {
+      Self::Print(val) => write!(f, "print {}", val),
+      Self::Call(name, args) => write!(f, "call {}({})", name, args.iter().format(", ")),
+      Self::Return(name, val) => write!(f, "return {} from {}", val, name),
+    }
+  }
+}
+
+/// Event Tagged with the Line that Caused It and the Comments on that Line
+#[derive(Debug, Clone)]
+pub struct TraceEvent {
+  pub event: Event,
+  pub line: u64,
+  pub comments: Vec<String>,
+}
+
+/// Line of the Instruction that Stopped a Program, with its Comments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Site {
+  pub line: u64,
+  pub comments: Vec<String>,
+  /// Location in the C0 source, when the program has `.loc` directives
+  pub loc: Option<SrcLoc>,
+}
+
+/// Result of Running a Program
+/// `site` is where it stopped when it did not return normally
+#[derive(Debug, PartialEq, Eq)]
+pub struct Outcome {
+  pub ret: ReturnType,
+  pub site: Option<Site>,
+  /// Everything the program printed through native functions, without any VM messages
+  pub output: String,
+}
+
+/// Input of a Program, Read from a String
+pub fn reader(input: &str) -> Box<dyn BufRead> {
+  Box::new(Cursor::new(input.as_bytes().to_vec()))
+}
+
+/// Number of Times Each Block of Each Function was Entered
+pub type BlockCounts = FxHashMap<(String, BlockID), u64>;
+
+/// Outcome of Running Two Programs and Comparing their Events
+pub struct Diff {
+  pub rets: (ReturnType, ReturnType),
+  /// First pair of events that differ, where `None` means that program had no more events
+  pub divergence: Option<(Option<TraceEvent>, Option<TraceEvent>)>,
+}
+
+impl Diff {
+  pub fn is_same(&self) -> bool {
+    self.rets.0 == self.rets.1 && self.divergence.is_none()
+  }
+}
+
 pub struct ProgContext {
   prog: ASM,
   start: Instant,
   timeout: u64,
+  trace: Option<RefCell<Vec<TraceEvent>>>,
+  counts: Option<RefCell<BlockCounts>>,
+  site: RefCell<Option<Site>>,
+  /// Read and Written by Native Functions
+  input: RefCell<Box<dyn BufRead>>,
+  output: RefCell<Vec<u8>>,
 }
 
 impl ProgContext {
+  fn new(prog: ASM, timeout: u64, input: Box<dyn BufRead>) -> Self {
+    ProgContext {
+      prog, start: Instant::now(), timeout, trace: None, counts: None, site: RefCell::new(None),
+      input: RefCell::new(input), output: RefCell::new(vec![]),
+    }
+  }
+
+  fn record(&self, event: Event, line: u64, comments: &[String]) {
+    if let Some(trace) = &self.trace {
+      trace.borrow_mut().push(TraceEvent { event, line, comments: comments.to_vec() });
+    }
+  }
+
+  /// Remember where the program stopped, keeping the innermost site when unwinding calls
+  fn stop(&self, ret: ReturnType, line: u64, comments: &[String], loc: &Option<SrcLoc>) -> ReturnType {
+    self.site.borrow_mut().get_or_insert_with(|| Site { line, comments: comments.to_vec(), loc: loc.clone() });
+    ret
+  }
+
   fn run_func(&self, name: String, args: Vec<i32>) -> ReturnType {
-    let Func { params, blocks, count, .. } = self.prog.get(&name).unwrap();
+    let func = self.prog.get(&name).unwrap();
+    let Func { params, blocks, count, .. } = func;
     let mut prev_block = None;
-    let mut curr_block = blocks.keys().min().unwrap().clone();
+    let mut curr_block = func.entry();
     let mut store = TempStore::new(count.unwrap());
 
     // Insert Arguments as Params
     for (param, arg) in params.iter().zip_eq(args.into_iter()) {
-      store.temps.insert(param.clone(), arg);
+      store.save(param, arg);
     }
 
     // Run Function Blocks
     'outer: loop {
+      let BasicBlock { preds, lines, branch, line_start, comments, .. } = blocks.get(&curr_block)
+        .unwrap_or_else(|| panic!("Block `{}` of `{}` Does Not Exist", func.label(curr_block), name));
+
       if self.start.elapsed().as_secs() > self.timeout {
-        return ReturnType::Timeout;
+        let loc = lines.first().map_or(&branch.loc, |instr| &instr.loc);
+        return self.stop(ReturnType::Timeout, *line_start, comments, loc);
       }
 
-      let BasicBlock { preds, lines, branch, .. } 
-        = blocks.get(&curr_block).unwrap();
+      if let Some(counts) = &self.counts {
+        *counts.borrow_mut().entry((name.clone(), curr_block)).or_insert(0) += 1;
+      }
 
       // Evaluate Operations
       for line in lines {
@@ -105,7 +217,7 @@ impl ProgContext {
             let src2_val = store.get(src2);
             store.save(dest, match op.eval(src1_val, src2_val) {
               Some(val) => val,
-              None => return ReturnType::DivByZero,
+              None => return self.stop(ReturnType::DivByZero, line.line, &line.comments, &line.loc),
             });
           },
 
@@ -129,7 +241,8 @@ impl ProgContext {
 
           InstrKind::Phi   { dest, srcs } => {
             if let Some(prev) = prev_block {
-              let pred_idx = preds.iter().position(|&x| x == prev).unwrap();
+              let pred_idx = preds.iter().position(|&x| x == prev)
+                .unwrap_or_else(|| panic!("Block `{}` is Not a Predecessor of `{}`", func.label(prev), func.label(curr_block)));
               let src = srcs.get(pred_idx).unwrap();
               store.save(dest, store.get(src));
     
@@ -139,31 +252,66 @@ impl ProgContext {
           },
 
           InstrKind::Call  { name, dest, src } => {
-            match self.run_func(name.clone(), src.iter().map(|x| store.get(x)).collect()) {
+            let args: Vec<_> = src.iter().map(|x| store.get(x)).collect();
+            self.record(Event::Call(name.clone(), args.clone()), line.line, &line.comments);
+
+            // Functions of the Program Shadow Native Ones
+            let ret = if self.prog.contains_key(name) {
+              self.run_func(name.clone(), args)
+            } else {
+              native::call(name, &args, &mut *self.input.borrow_mut(), &mut *self.output.borrow_mut())
+            };
+
+            match ret {
               ReturnType::Return(val) => if let Some(dest) = dest {
                 store.save(dest,  val);
               },
-              other => return other,
+              other => return self.stop(other, line.line, &line.comments, &line.loc),
             }
           },
 
+          InstrKind::Print { value } if self.trace.is_some() => {
+            self.record(Event::Print(store.get(value)), line.line, &line.comments);
+          },
+
           InstrKind::Print { value } => {
-            println!("[{}] Line {}: {} = {}", Local::now().time().format("%H:%M:%S"), line.line, value, store.get(value));
+            print!("[{}] Line {}", Local::now().time().format("%H:%M:%S"), line.line);
+            if let Some(loc) = &line.loc {
+              print!(" ({})", loc);
+            }
+            print!(": {} = {}", value, store.get(value));
+            for comment in line.comments.iter() {
+              print!("  //{}", comment);
+            }
+            println!();
           },
 
           InstrKind::Dump => {
-            println!("[{}] Line {}: Dump of All Temps", line.line, Local::now().time().format("%H:%M:%S"));
+            println!("[{}] Line {}: Dump of All Temps", Local::now().time().format("%H:%M:%S"), line.line);
             store.dump();
           },
 
           InstrKind::Nop => {},
+
+          InstrKind::Ext { ext, dest, srcs } => {
+            let vals: Vec<_> = srcs.iter().map(|src| store.get(src)).collect();
+            match ext.exec(&vals) {
+              Ok(val) => if let Some(dest) = dest {
+                store.save(dest, val);
+              },
+              Err(ret) => return self.stop(ret, line.line, &line.comments, &line.loc),
+            }
+          },
         }
       }
 
       // Path Handling
       match &branch.kind {
-        BranchKind::Ret(None) => return ReturnType::Return(0),  // Doesnt Matter if No Dest
-        BranchKind::Ret(Some(ret)) => return ReturnType::Return(store.get(ret)),
+        BranchKind::Ret(ret) => {
+          let val = ret.as_ref().map_or(0, |ret| store.get(ret));  // Doesnt Matter if No Dest
+          self.record(Event::Return(name, val), branch.line, &branch.comments);
+          return ReturnType::Return(val);
+        },
         BranchKind::Jump(bidx) => { 
           prev_block = Some(curr_block);
           curr_block = *bidx;
@@ -174,7 +322,7 @@ impl ProgContext {
             Cond::BinOp(src1, op, src2) =>
               match op.eval(store.get(src1), store.get(src2)) {
                 Some(val) => val,
-                None => return ReturnType::DivByZero,
+                None => return self.stop(ReturnType::DivByZero, branch.line, &branch.comments, &branch.loc),
               },
             Cond::Value(src) => store.get(src),
           };
@@ -187,8 +335,39 @@ impl ProgContext {
     }
   }
 
-  pub fn run(prog: ASM, timeout: u64) -> ReturnType {
-    let ctx = ProgContext { prog, start: Instant::now(), timeout };
-    ctx.run_func("main".to_string(), vec![])
-  }  
+  /// Run the program, feeding `input` to its native functions and capturing their output
+  pub fn run(prog: ASM, timeout: u64, input: Box<dyn BufRead>) -> Outcome {
+    let ctx = ProgContext::new(prog, timeout, input);
+    let ret = ctx.run_func("main".to_string(), vec![]);
+    let output = String::from_utf8_lossy(&ctx.output.into_inner()).into_owned();
+    Outcome { ret, site: ctx.site.into_inner(), output }
+  }
+
+  /// Run the program while counting how often each block is entered
+  pub fn run_profiled(prog: ASM, timeout: u64, input: Box<dyn BufRead>) -> (ReturnType, BlockCounts) {
+    let ctx = ProgContext { counts: Some(RefCell::new(FxHashMap::default())), ..ProgContext::new(prog, timeout, input) };
+    let ret = ctx.run_func("main".to_string(), vec![]);
+    (ret, ctx.counts.unwrap().into_inner())
+  }
+
+  /// Run the program while recording its events
+  /// Prints are recorded instead of being written to stdout
+  pub fn run_traced(prog: ASM, timeout: u64, input: Box<dyn BufRead>) -> (ReturnType, Vec<TraceEvent>) {
+    let ctx = ProgContext { trace: Some(RefCell::new(vec![])), ..ProgContext::new(prog, timeout, input) };
+    let ret = ctx.run_func("main".to_string(), vec![]);
+    (ret, ctx.trace.unwrap().into_inner())
+  }
+
+  /// Run both programs on the same input and find the first event where they diverge
+  pub fn diff(left: ASM, right: ASM, timeout: u64, input: &str) -> Diff {
+    let (left_ret, left_trace) = Self::run_traced(left, timeout, reader(input));
+    let (right_ret, right_trace) = Self::run_traced(right, timeout, reader(input));
+
+    let divergence = left_trace.into_iter()
+      .zip_longest(right_trace)
+      .find(|pair| !matches!(pair.as_ref().both(), Some((left, right)) if left.event == right.event))
+      .map(|pair| pair.map_any(Some, Some).or(None, None));
+
+    Diff { rets: (left_ret, right_ret), divergence }
+  }
 }
@@ -0,0 +1,91 @@
+use std::fmt::{self, Debug, Formatter};
+use std::ops::Deref;
+use std::sync::Arc;
+
+use fxhash::FxHashMap;
+
+use crate::exec::ReturnType;
+
+
+/// Operands of an Extension Instruction
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Shape {
+  /// Whether the instruction writes a temp, as in `#t = <mnemonic> ...`
+  pub dest: bool,
+  /// Number of source operands, or `None` for any number
+  pub srcs: Option<usize>,
+}
+
+/// Instruction Added by a Downstream Crate
+/// It is written as `<mnemonic> <operand>*` or `#t = <mnemonic> <operand>*`,
+/// where each operand is a temp or a constant.
+pub trait InstrExt: Send + Sync {
+  /// Name the instruction is written with
+  /// Keywords such as `print` or `call` are lexed first, so they can not be used
+  fn mnemonic(&self) -> &'static str;
+
+  /// Whether it has a destination and how many source operands it takes
+  fn shape(&self) -> Shape;
+
+  /// Semantic check run before the program executes
+  /// Gets the value of each source operand that is a constant, and `None` for temps
+  fn check(&self, _consts: &[Option<i32>]) -> Result<(), String> {
+    Ok(())
+  }
+
+  /// Execute the instruction on the values of its source operands
+  /// Returns the value written to the destination (ignored without one),
+  /// or the result the whole program stops with, such as `ReturnType::DivByZero`
+  fn exec(&self, srcs: &[i32]) -> Result<i32, ReturnType>;
+}
+
+
+/// Registered Extension Instruction, Compared by Mnemonic
+#[derive(Clone)]
+pub struct Ext(Arc<dyn InstrExt>);
+
+impl Deref for Ext {
+  type Target = dyn InstrExt;
+
+  fn deref(&self) -> &Self::Target {
+    self.0.as_ref()
+  }
+}
+
+impl PartialEq for Ext {
+  fn eq(&self, other: &Self) -> bool {
+    self.mnemonic() == other.mnemonic()
+  }
+}
+
+impl Eq for Ext {}
+
+impl Debug for Ext {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.mnemonic())
+  }
+}
+
+
+/// Set of Extension Instructions the Parser Recognizes
+#[derive(Clone, Default)]
+pub struct Extensions(FxHashMap<&'static str, Ext>);
+
+impl Extensions {
+  /// Add an instruction, replacing any other with the same mnemonic
+  pub fn register(&mut self, instr: impl InstrExt + 'static) {
+    self.0.insert(instr.mnemonic(), Ext(Arc::new(instr)));
+  }
+
+  pub(crate) fn get(&self, mnemonic: &str) -> Option<&Ext> {
+    self.0.get(mnemonic)
+  }
+}
+
+impl Debug for Extensions {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let mut mnemonics: Vec<_> = self.0.keys().collect();
+    mnemonics.sort();
+    f.debug_set().entries(mnemonics).finish()
+  }
+}
@@ -15,30 +15,103 @@ macro_rules! set {
 }
 
 mod error;
+pub mod ext;
+pub mod warning;
 pub mod args;
 mod asm;
 mod exec;
+mod native;
 mod ops;
 mod parser;
 mod analysis;
+mod dot;
+mod printer;
 mod rename;
 
+use std::fmt::{Display, Formatter, Error as FmtError};
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufRead, BufReader, Read};
 
 
-use analysis::sem_analysis;
+use derives::DebugFromDisplay;
+use itertools::Itertools;
+
+use analysis::{liveness, sem_analysis};
+use analysis::regalloc::{check_conflicts, infer_map, parse_map};
+pub use analysis::regalloc::{AllocError, AllocFile};
 use args::Config;
-use exec::ProgContext;
-pub use exec::ReturnType;
+use asm::ASM;
+pub use asm::instr::SrcLoc;
+use exec::{reader, ProgContext};
+pub use exec::{Diff, Event, Outcome, ReturnType, Site, TraceEvent};
 pub use error::{Error, ErrorTrait};
+use printer::Printer;
+pub use printer::Radix;
 use rename::rename;
+use warning::{Level, Warning};
 
 
+/// Run the program without any input
 pub fn run(config: &Config, file_str: &str) -> Result<ReturnType, Error> {
-  let parse_res = parser::parse(file_str);
+  run_with_input(config, file_str, "").map(|outcome| outcome.ret)
+}
+
+/// Run the program with `input` as its stdin, capturing its output in the `Outcome`
+pub fn run_with_input(config: &Config, file_str: &str, input: &str) -> Result<Outcome, Error> {
+  run_with_warnings(config, file_str, reader(input), &mut vec![])
+}
+
+/// Parsed Program
+/// Printing it gives the canonical form of the program, which parses back to an equal program
+/// with the same extensions
+#[derive(Clone, PartialEq, DebugFromDisplay)]
+pub struct Program(ASM);
+
+impl Display for Program {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+    Printer::new(f).asm(&self.0)
+  }
+}
+
+impl Program {
+  /// Canonical form of the program with constants spelled in `radix`
+  pub fn format(&self, radix: Radix) -> String {
+    let mut out = String::new();
+    Printer::with_radix(&mut out, radix).asm(&self.0).unwrap();
+    out
+  }
+}
+
+/// Parse the program without checking it
+pub fn parse(file_str: &str) -> Result<Program, Error> {
+  parse_with(&ext::Extensions::default(), file_str)
+}
+
+/// Parse the program without checking it, accepting the instructions registered in `extensions`
+pub fn parse_with(extensions: &ext::Extensions, file_str: &str) -> Result<Program, Error> {
+  parser::parse(file_str, extensions).map(Program).map_err(Error::ParseError)
+}
+
+/// Parse and check the program, collecting the warnings that are not silenced into `warnings`
+/// The first warning promoted to an error with `-D` is returned as the error
+pub fn check(config: &Config, file_str: &str, warnings: &mut Vec<Warning>) -> Result<Program, Error> {
+  let parse_res = parser::parse(file_str, &config.extensions);
   let abs = parse_res.map_err(|e| Error::ParseError(e))?;  
-  sem_analysis(&abs, config.ssa).map_err(|e| Error::SemError(e))?;
+  let enabled = |lint| config.lint_level(lint) != Level::Allow;
+  sem_analysis(&abs, config.ssa, &enabled, warnings).map_err(|e| Error::SemError(e))?;
+
+  // Apply Lint Levels
+  warnings.retain(|warning| config.lint_level(warning.lint()) != Level::Allow);
+  if let Some(idx) = warnings.iter().position(|warning| config.lint_level(warning.lint()) == Level::Deny) {
+    return Err(Error::Lint(warnings.remove(idx)));
+  }
+
+  Ok(Program(abs))
+}
+
+/// Run the program reading from `input`, collecting the warnings that are not silenced into `warnings`
+pub fn run_with_warnings(config: &Config, file_str: &str, input: Box<dyn BufRead>, warnings: &mut Vec<Warning>) -> Result<Outcome, Error> {
+  let Program(abs) = check(config, file_str, warnings)?;
 
   // TODO: Verbose
   // for (_, func) in abs_asm.iter() {
@@ -49,7 +122,68 @@ pub fn run(config: &Config, file_str: &str) -> Result<ReturnType, Error> {
   let abs = rename(abs);
 
   // TODO: Any errors here?
-  Ok(ProgContext::run(abs, config.timeout.unwrap_or(1000)))
+  Ok(ProgContext::run(abs, config.timeout.unwrap_or(1000), input))
+}
+
+/// Live-in and live-out sets of every block in the program, without running it
+pub fn liveness_report(config: &Config, file_str: &str, warnings: &mut Vec<Warning>) -> Result<String, Error> {
+  let Program(abs) = check(config, file_str, warnings)?;
+
+  Ok(abs.values()
+    .map(|func| liveness(func).report(func))
+    .join("\n"))
+}
+
+/// Check that `post` is a valid register allocation of `pre`, running both on `input`
+/// Without a mapping file, the location of each temp is inferred from the shape of the programs
+pub fn validate_regalloc(config: &Config, pre: &Program, post: &Program, map_str: Option<&str>, input: &str) -> Vec<AllocError> {
+  let map = match map_str.map_or_else(|| infer_map(&pre.0, &post.0), parse_map) {
+    Ok(map) => map,
+    Err(err) => return vec![err],
+  };
+
+  let mut errors = check_conflicts(&pre.0, &map);
+
+  // Both Programs Should Behave the Same
+  let timeout = config.timeout.unwrap_or(1000);
+  let (pre_ret, pre_trace) = ProgContext::run_traced(rename(pre.0.clone()), timeout, reader(input));
+  let (post_ret, post_trace) = ProgContext::run_traced(rename(post.0.clone()), timeout, reader(input));
+
+  let prints = |trace: Vec<TraceEvent>| trace.into_iter().filter_map(|TraceEvent { event, line, .. }| match event {
+    Event::Print(val) => Some((line, val)),
+    _ => None,
+  }).collect_vec();
+
+  let mismatch = prints(pre_trace).into_iter()
+    .zip_longest(prints(post_trace))
+    .find(|pair| !matches!(pair.as_ref().both(), Some(((_, pre_val), (_, post_val))) if pre_val == post_val));
+  if let Some(pair) = mismatch {
+    let (pre_print, post_print) = pair.map_any(Some, Some).or(None, None);
+    errors.push(AllocError::PrintMismatch(pre_print, post_print));
+  }
+
+  if pre_ret != post_ret {
+    errors.push(AllocError::ResultMismatch(pre_ret, post_ret));
+  }
+
+  errors
+}
+
+/// Run two programs on `input`, such as before and after an optimization, and compare what they do
+pub fn diff(config: &Config, left: &Program, right: &Program, input: &str) -> Diff {
+  ProgContext::diff(rename(left.0.clone()), rename(right.0.clone()), config.timeout.unwrap_or(1000), input)
+}
+
+/// Graphviz DOT graph of each function, in source order, as `(name, graph)` pairs
+/// With `--dot-counts`, the program is run on `input` first to color blocks by how often they execute
+pub fn cfg_dot(config: &Config, program: &Program, input: &str) -> Vec<(String, String)> {
+  let counts = config.dot_counts.then(|| {
+    ProgContext::run_profiled(rename(program.0.clone()), config.timeout.unwrap_or(1000), reader(input)).1
+  });
+
+  program.0.values()
+    .map(|func| (func.name.clone(), dot::func_dot(func, counts.as_ref())))
+    .collect()
 }
 
 pub fn run_wrapper(config: &Config) -> Result<ReturnType, Error> {
@@ -0,0 +1,104 @@
+use std::io::{BufRead, Write};
+
+use itertools::Itertools;
+
+use crate::exec::ReturnType;
+
+
+/// Native Functions of the C0 Runtime Library, with their Number of Arguments (`None` for Any)
+/// They are called when the program does not define a function with the same name.
+/// The VM only has integers, so `print` and `println` print their arguments as numbers,
+/// and `readline` reads a line holding a single integer.
+pub const NATIVES: [(&str, Option<usize>); 12] = [
+  ("print", None),
+  ("println", None),
+  ("printint", Some(1)),
+  ("printbool", Some(1)),
+  ("printchar", Some(1)),
+  ("flush", Some(0)),
+  ("eof", Some(0)),
+  ("readline", Some(0)),
+  ("readint", Some(0)),
+  ("abs", Some(1)),
+  ("min", Some(2)),
+  ("max", Some(2)),
+];
+
+/// Number of arguments of a native function, or `None` if there is no such function
+pub fn arity(name: &str) -> Option<Option<usize>> {
+  NATIVES.iter().find(|(native, _)| *native == name).map(|(_, arity)| *arity)
+}
+
+
+/// Whether there is no more input
+fn at_eof(input: &mut dyn BufRead) -> bool {
+  input.fill_buf().map_or(true, |buf| buf.is_empty())
+}
+
+/// Next whitespace-separated word of the input
+fn read_word(input: &mut dyn BufRead) -> Option<String> {
+  let mut word = vec![];
+
+  loop {
+    let buf = input.fill_buf().ok()?;
+    if buf.is_empty() {
+      break;
+    }
+
+    let skip = if word.is_empty() { buf.iter().take_while(|byte| byte.is_ascii_whitespace()).count() } else { 0 };
+    let len = buf[skip..].iter().take_while(|byte| !byte.is_ascii_whitespace()).count();
+    word.extend_from_slice(&buf[skip..skip + len]);
+
+    let done = skip + len < buf.len() && (len > 0 || !word.is_empty());
+    input.consume(skip + len);
+    if done {
+      break;
+    }
+  }
+
+  // The Newline Ending the Word Belongs to It, so a Later `readline` Starts on the Next Line
+  if input.fill_buf().ok()?.starts_with(b"\n") {
+    input.consume(1);
+  }
+
+  String::from_utf8(word).ok().filter(|word| !word.is_empty())
+}
+
+fn read_line(input: &mut dyn BufRead) -> Option<String> {
+  let mut line = String::new();
+  match input.read_line(&mut line) {
+    Ok(0) | Err(_) => None,
+    Ok(_) => Some(line),
+  }
+}
+
+
+/// Run a native function, reading from `input` and writing to `out`
+/// Reading something that is not an integer, or past the end of the input, aborts the program
+pub fn call(name: &str, args: &[i32], input: &mut dyn BufRead, out: &mut dyn Write) -> ReturnType {
+  let written = match (name, args) {
+    ("print", args) => write!(out, "{}", args.iter().format(" ")),
+    ("println", args) => writeln!(out, "{}", args.iter().format(" ")),
+    ("printint", [val]) => write!(out, "{}", val),
+    ("printbool", [val]) => write!(out, "{}", *val != 0),
+    ("printchar", [val]) => write!(out, "{}", char::from_u32(*val as u32).unwrap_or(char::REPLACEMENT_CHARACTER)),
+    ("flush", []) => out.flush(),
+
+    ("eof", []) => return ReturnType::Return(at_eof(input) as i32),
+    ("readint", []) => return read_word(input)
+      .and_then(|word| word.parse().ok())
+      .map_or(ReturnType::Abort, ReturnType::Return),
+    ("readline", []) => return read_line(input)
+      .and_then(|line| line.trim().parse().ok())
+      .map_or(ReturnType::Abort, ReturnType::Return),
+
+    ("abs", [val]) => return ReturnType::Return(val.wrapping_abs()),
+    ("min", [lval, rval]) => return ReturnType::Return(*lval.min(rval)),
+    ("max", [lval, rval]) => return ReturnType::Return(*lval.max(rval)),
+    _ => panic!("No Native Function `{}` Taking {} Argument(s)", name, args.len()),
+  };
+
+  // Output Errors (Such as a Closed Pipe) do not Change the Result
+  let _ = written;
+  ReturnType::Return(0)
+}
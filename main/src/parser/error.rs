@@ -1,6 +1,7 @@
 use std::ops::Range;
 
 use crate::error::ErrorTrait;
+use crate::ext::Shape;
 use super::lexer::Token;
 
 
@@ -9,6 +10,7 @@ pub enum ParseErrorKind {
   UnknownInstr(String),
   InvalidFuncName(String),
   FuncNeedBlock,
+  ExtShape(String, Shape),
 
   NoMatch(Token, Token),
   NoTemp(Token),
@@ -28,6 +30,7 @@ impl ErrorTrait for ParseError {
       ParseErrorKind::UnknownInstr(_) => 0,
       ParseErrorKind::InvalidFuncName(_) => 1,
       ParseErrorKind::FuncNeedBlock => 2,
+      ParseErrorKind::ExtShape(_, _) => 3,
       ParseErrorKind::NoMatch(_, _) => 94,
       ParseErrorKind::NoTemp(_) => 95,
       ParseErrorKind::NoBlock(_) => 96,
@@ -42,6 +45,7 @@ impl ErrorTrait for ParseError {
       ParseErrorKind::UnknownInstr(_) => "Unknown or Invalid Instruction(s)",
       ParseErrorKind::InvalidFuncName(_) => "Invalid Expected Function Name(s)",
       ParseErrorKind::FuncNeedBlock => "Function(s) Need at Least 1 Block",
+      ParseErrorKind::ExtShape(_, _) => "Wrong Operands for Extension Instruction",
 
       ParseErrorKind::NoMatch(_, _) => "No Match",
       ParseErrorKind::NoTemp(_) => "Require a Temp Label",
@@ -55,6 +59,10 @@ impl ErrorTrait for ParseError {
   fn label(&self) -> Option<(String, Range<usize>)> {
     Some((match &self.0 {
       ParseErrorKind::UnknownInstr(instr) => format!("`{}` is not a valid instruction", instr),
+      ParseErrorKind::ExtShape(mnemonic, Shape { dest, srcs }) => format!(
+        "`{}` {} a destination and takes {} operand(s)",
+        mnemonic, if *dest { "needs" } else { "has no" }, srcs.map_or("any number of".to_string(), |n| n.to_string())
+      ),
       _ => "TODO".to_string()
     }, self.1.clone()))
 
@@ -78,6 +86,7 @@ impl ErrorTrait for ParseError {
       ParseErrorKind::UnknownInstr(_) => Some("See the `FORMAT.md` for a list of valid instructions.".to_string()),
       ParseErrorKind::InvalidFuncName(_) => Some("Invalid Expected Function Name(s)".to_string()),
       ParseErrorKind::FuncNeedBlock => Some("Function(s) Need at Least 1 Block".to_string()),
+      ParseErrorKind::ExtShape(_, _) => None,
 
       ParseErrorKind::NoMatch(_, _) => None,
       ParseErrorKind::NoTemp(_) => None,
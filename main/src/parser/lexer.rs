@@ -2,13 +2,11 @@ use std::str::FromStr;
 
 use logos::{Logos, Lexer};
 
-use crate::asm::{instr::TempID, reg::Register};
+use crate::asm::{blocks::BlockID, instr::{SrcLoc, TempID}, reg::Register};
 
 
-// TODO: Consider dynamic extensibility of lexer
-// If we want to allow custom instructions, then we need to find some way
-// to add onto the list of tokens. This might require using a custom solution
-// instead of the Logos library.
+// Custom instructions do not need their own tokens: their mnemonics are lexed
+// as `Id`s and looked up by the parser in the registered `ext::Extensions`.
 
 /// List of Tokens to Lex For + Autogenerated Lexer
 /// Check out the Logos Library for the Correct Syntax
@@ -62,13 +60,23 @@ pub enum Token {
 
   #[token("\n")]   NewLine,
 
+  // Comments are Kept for the Formatter
+  #[regex(r"//[^\n]*", |lex| lex.slice()[2..].trim_end().to_string())]
+  Comment(String),
+
+  #[regex(r"\.loc[ \t]+[^ \t\n:]+:[0-9]+(:[0-9]+)?", parse_loc)]
+  Loc(SrcLoc),
+
   // Identifiers
-  #[regex(r"\#(0|[1-9][0-9]*)", parse_temp)] 
+  #[regex(r"\#(0|[1-9][0-9]*|[a-z][a-z0-9]*)", parse_temp)] 
   Temp(TempID),
 
   #[regex(r"@(0|[1-9][0-9]*)", parse_udec)] 
   Block(u64),
 
+  #[regex(r"@[a-zA-Z_][a-zA-Z0-9_.]*", |lex| lex.slice()[1..].to_string())]
+  Label(String),
+
   #[regex(r"(-?)(0|[1-9][0-9]*)", parse_dec)]
   #[regex(r"0[xX][0-9a-fA-F]+", parse_hex)]
   Const(i32),
@@ -77,7 +85,6 @@ pub enum Token {
   Id(String),
 
   #[regex(r"[ \t\f\v\r]+", logos::skip)]
-  #[regex(r"//[^\n]*", logos::skip)]
   #[error]
   Error,
 }
@@ -93,6 +100,20 @@ fn parse_temp(lex: &mut Lexer<Token>) -> Option<TempID> {
 }
 
 
+/// Parse the `file:line[:col]` of a `.loc` Directive
+fn parse_loc(lex: &mut Lexer<Token>) -> Option<SrcLoc> {
+  let mut parts = lex.slice()[4..].trim_start().split(':');
+  let file = parts.next()?.to_string();
+  let line = parts.next()?.parse().ok()?;
+  let col = match parts.next() {
+    Some(col) => Some(col.parse().ok()?),
+    None => None,
+  };
+
+  Some(SrcLoc { file, line, col })
+}
+
+
 /// Parse Numeral Number Strings to i32 Integers
 /// Must specially handle INT_MIN because the minus sign is ignored
 fn parse_dec(lex: &mut Lexer<Token>) -> i32 {
@@ -132,8 +153,9 @@ fn parse_hex(lex: &mut logos::Lexer<Token>) -> Option<i32> {
 }
 
 
+/// Block Numbers Must Stay Below the IDs Reserved for Named Labels
 fn parse_udec(lex: &mut Lexer<Token>) -> Option<u64> {
   let slice = lex.slice();
   let n: u64 = slice[1..].parse().ok()?; // skip '#' or '@'
-  Some(n)
+  (n < BlockID::LABEL_BASE).then_some(n)
 }
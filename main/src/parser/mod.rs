@@ -6,8 +6,9 @@ use fxhash::FxHashMap;
 use logos::{Logos, Lexer};
 
 use crate::asm::ASM;
-use crate::asm::blocks::{Func, BasicBlock, BlockID, Branch, BranchKind, Cond};
-use crate::asm::instr::{InstrKind, Instr, Operand, Temp};
+use crate::ext::Extensions;
+use crate::asm::blocks::{Func, BasicBlock, BlockID, Blocks, Branch, BranchKind, Cond};
+use crate::asm::instr::{InstrKind, Instr, Operand, SrcLoc, Temp};
 
 use lexer::Token;
 use error::{ParseError, ParseResult};
@@ -23,6 +24,11 @@ struct Parser<'a> {
   // peeked_slice: Option<&'a str>,
   lexer: Lexer<'a, Token>,
   cur_line: u64,
+  comments: Vec<(u64, String)>,
+  locs: Vec<(u64, SrcLoc)>,
+  // Named Labels of the Current Function
+  labels: FxHashMap<String, BlockID>,
+  ext: &'a Extensions,
 }
 
 impl<'a> Parser<'a> {
@@ -43,12 +49,24 @@ impl<'a> Parser<'a> {
 
 
   // ---------------------------- HELPER FUNCTIONS ----------------------------
+  /// Get the next token from the lexer that is not a comment or `.loc` directive
+  /// These are set aside with their line to be attached once parsing is done
+  fn next_token(&mut self) -> Option<Token> {
+    loop {
+      match self.lexer.next() {
+        Some(Token::Comment(text)) => self.comments.push((self.cur_line, text)),
+        Some(Token::Loc(loc)) => self.locs.push((self.cur_line, loc)),
+        tok => return tok,
+      }
+    }
+  }
+
   /// Get the next token from the lexer
   /// Returns EOF when no more tokens
   fn token(&mut self) -> ParseResult<Token> {
     let tok = match self.peeked.take() {
       Some(v) => v,
-      None => self.lexer.next(),
+      None => self.next_token(),
     };
 
     tok.ok_or(self.err(ParseErrorKind::EOF))
@@ -58,8 +76,11 @@ impl<'a> Parser<'a> {
   /// This function NEVER moves the lexer, and can be called multiple times
   /// and will return the same result.
   fn peek(&mut self) -> ParseResult<&Token> {
-    let iter = &mut self.lexer;
-    if let Some(peek) = self.peeked.get_or_insert_with(|| iter.next()).as_ref() {
+    if self.peeked.is_none() {
+      self.peeked = Some(self.next_token());
+    }
+
+    if let Some(peek) = self.peeked.as_ref().unwrap().as_ref() {
       Ok(peek)
     } else {
       Err(ParseError(ParseErrorKind::EOF, self.lexer.span()))
@@ -107,12 +128,15 @@ impl<'a> Parser<'a> {
     }
   }
 
+  // Expect next token to be a block number or a named label
   fn block(&mut self) -> ParseResult<BlockID> {
-    let tok  = self.token()?;
-    if let Token::Block(val) = tok {
-      Ok(BlockID(val))
-    } else {
-      Err(self.err(ParseErrorKind::NoBlock(tok)))
+    match self.token()? {
+      Token::Block(val) => Ok(BlockID(val)),
+      Token::Label(label) => {
+        let next = BlockID(BlockID::LABEL_BASE + self.labels.len() as u64);
+        Ok(*self.labels.entry(label).or_insert(next))
+      },
+      tok => Err(self.err(ParseErrorKind::NoBlock(tok))),
     }
   }
 
@@ -126,6 +150,17 @@ impl<'a> Parser<'a> {
     }
   }
 
+  /// Name of a Called Function, which may be the `print` Native Function
+  fn callee(&mut self) -> ParseResult<String> {
+    match self.peek()? {
+      Token::Print => {
+        self.token()?;
+        Ok("print".to_string())
+      },
+      _ => self.name(),
+    }
+  }
+
 
   // ---------------------------- PARSER FUNCTIONS ----------------------------
   fn operand(&mut self) -> ParseResult<Operand> {
@@ -140,7 +175,7 @@ impl<'a> Parser<'a> {
     match self.token()? {
       Token::NewLine => {
         self.cur_line += 1;
-        Ok(Instr { kind: InstrKind::Mov { dest, src: lsrc }, line: self.cur_line - 1 })
+        Ok(Instr { kind: InstrKind::Mov { dest, src: lsrc }, line: self.cur_line - 1, comments: vec![], loc: None })
       },
       
       tok => {
@@ -149,11 +184,32 @@ impl<'a> Parser<'a> {
 
         self.munch(Token::NewLine)?;
         self.cur_line += 1;
-        Ok(Instr { kind: InstrKind::BinOp { dest, op, src1: lsrc, src2 }, line: self.cur_line - 1 })
+        Ok(Instr { kind: InstrKind::BinOp { dest, op, src1: lsrc, src2 }, line: self.cur_line - 1, comments: vec![], loc: None })
       }
     }
   }
 
+  /// Instruction registered as an extension, after its destination (if any) and mnemonic
+  fn ext_instr(&mut self, dest: Option<Temp>, mnemonic: String) -> ParseResult<Instr> {
+    let ext = match self.ext.get(&mnemonic) {
+      Some(ext) => ext.clone(),
+      None => return Err(self.err_to_line_end(ParseErrorKind::UnknownInstr(mnemonic))),
+    };
+
+    let start = self.lexer.span().start;
+    let mut srcs = vec![];
+    while !matches!(self.peek()?, Token::NewLine) {
+      srcs.push(self.operand()?);
+    }
+
+    let shape = ext.shape();
+    if shape.dest != dest.is_some() || shape.srcs.is_some_and(|count| count != srcs.len()) {
+      return Err(ParseError(ParseErrorKind::ExtShape(mnemonic, shape), start..self.lexer.span().end));
+    }
+
+    Ok(Instr { kind: InstrKind::Ext { ext, dest, srcs }, line: self.cur_line, comments: vec![], loc: None })
+  }
+
   fn instr(&mut self) -> ParseResult<Instr> {
     match self.token()? {
       Token::Temp(val) => {
@@ -168,7 +224,9 @@ impl<'a> Parser<'a> {
 
             Ok(Instr { 
               kind: InstrKind::UnOp { dest, src, op: unop_code(op, self.lexer.span())? }, 
-              line: self.cur_line - 1 
+              line: self.cur_line - 1,
+              comments: vec![],
+              loc: None,
             })
           },
 
@@ -182,23 +240,25 @@ impl<'a> Parser<'a> {
               }
             }
 
-            Ok(Instr { kind: InstrKind::Phi { dest, srcs }, line: self.cur_line })
+            Ok(Instr { kind: InstrKind::Phi { dest, srcs }, line: self.cur_line, comments: vec![], loc: None })
           },
 
           Token::Call => {
-            let name = self.name()?;
+            let name = self.callee()?;
             let mut params = vec![];
             while !matches!(self.peek()?, Token::NewLine) {
               params.push(self.operand()?);
             }
             
-            Ok(Instr { kind: InstrKind::Call { dest: Some(dest), name, src: params }, line: self.cur_line })
+            Ok(Instr { kind: InstrKind::Call { dest: Some(dest), name, src: params }, line: self.cur_line, comments: vec![], loc: None })
           },
 
           Token::Temp(val) =>
             self.mov_binop_instr(dest, Operand::Temp(Temp(val))),
           Token::Const(val) =>
             self.mov_binop_instr(dest, Operand::Const(val)),
+          Token::Id(mnemonic) =>
+            self.ext_instr(Some(dest), mnemonic),
           
           _ => Err(self.err_to_line_end(ParseErrorKind::UnknownInstr(self.lexer.slice().to_string()))),
         }
@@ -207,32 +267,34 @@ impl<'a> Parser<'a> {
       Token::If => {
         let cond = self.operand()?;
         let block = self.block()?;
-        Ok(Instr { kind: InstrKind::If { cond, block }, line: self.cur_line })
+        Ok(Instr { kind: InstrKind::If { cond, block }, line: self.cur_line, comments: vec![], loc: None })
       },
 
       Token::Print => {
         let value = self.operand()?;
-        Ok(Instr { kind: InstrKind::Print { value }, line: self.cur_line })
+        Ok(Instr { kind: InstrKind::Print { value }, line: self.cur_line, comments: vec![], loc: None })
       },
 
       Token::Dump => {
-        Ok(Instr { kind: InstrKind::Dump, line: self.cur_line })
+        Ok(Instr { kind: InstrKind::Dump, line: self.cur_line, comments: vec![], loc: None })
       },
 
       Token::Nop => {
-        Ok(Instr { kind: InstrKind::Nop, line: self.cur_line })
+        Ok(Instr { kind: InstrKind::Nop, line: self.cur_line, comments: vec![], loc: None })
       },
 
       Token::Call => {
-        let name = self.name()?;
+        let name = self.callee()?;
         let mut params = vec![];
         while !matches!(self.peek()?, Token::NewLine) {
           params.push(self.operand()?);
         }
         
-        Ok(Instr { kind: InstrKind::Call { dest: None, name, src: params }, line: self.cur_line })
+        Ok(Instr { kind: InstrKind::Call { dest: None, name, src: params }, line: self.cur_line, comments: vec![], loc: None })
       },
 
+      Token::Id(mnemonic) => self.ext_instr(None, mnemonic),
+
       _ => Err(self.err_to_line_end(ParseErrorKind::UnknownInstr(self.lexer.slice().to_string()))),
     }
   }
@@ -258,13 +320,13 @@ impl<'a> Parser<'a> {
           Some(self.operand()?)
         };
 
-        Branch { kind: BranchKind::Ret(temp_opt), line: self.cur_line }
+        Branch { kind: BranchKind::Ret(temp_opt), line: self.cur_line, comments: vec![], loc: None }
       },
 
       Token::Cmp => {
         let cond = {
           let loper = self.operand()?;
-          if matches!(self.peek()?, Token::Block(_)) {
+          if matches!(self.peek()?, Token::Block(_) | Token::Label(_)) {
             Cond::Value(loper)
 
           } else {
@@ -276,10 +338,10 @@ impl<'a> Parser<'a> {
 
         let lblock = self.block()?;
         let rblock = self.block()?;
-        Branch { kind: BranchKind::Cond(cond, lblock, rblock), line: self.cur_line }
+        Branch { kind: BranchKind::Cond(cond, lblock, rblock), line: self.cur_line, comments: vec![], loc: None }
       },
 
-      Token::Jmp => Branch { kind: BranchKind::Jump(self.block()?), line: self.cur_line },
+      Token::Jmp => Branch { kind: BranchKind::Jump(self.block()?), line: self.cur_line, comments: vec![], loc: None },
       _ => unreachable!(),
     };
 
@@ -287,8 +349,8 @@ impl<'a> Parser<'a> {
     Ok((lines, branch))
   }
 
-  fn blocks(&mut self) -> ParseResult<FxHashMap<BlockID, BasicBlock>> {
-    let mut blocks = FxHashMap::default();
+  fn blocks(&mut self) -> ParseResult<Blocks> {
+    let mut blocks = Blocks::default();
 
     loop {
       match self.peek() {
@@ -296,7 +358,7 @@ impl<'a> Parser<'a> {
           return if blocks.is_empty() { Err(self.err(ParseErrorKind::FuncNeedBlock)) } else { Ok(blocks) }
         },
 
-        Ok(Token::Block(_)) => {
+        Ok(Token::Block(_) | Token::Label(_)) => {
           let line_start = self.cur_line;
           let id = self.block()?;
           // Parse List of Predecessors
@@ -307,7 +369,7 @@ impl<'a> Parser<'a> {
 
           self.skip_newlines()?;
           let (lines, branch) = self.block_inner()?;
-          blocks.insert(id, BasicBlock { id, preds, lines, branch, line_start });
+          blocks.insert(id, BasicBlock { id, preds, lines, branch, line_start, comments: vec![] });
         },
 
         Ok(tok) => {
@@ -333,22 +395,31 @@ impl<'a> Parser<'a> {
     self.skip_newlines()?;
 
     // Parser Blocks (or single block)
-    let blocks = if matches!(self.peek()?, Token::Temp(_) | Token::Ret) { 
+    // An Extension Instruction can Start an Implicit Block, Unlike a Function Name
+    let ext = self.ext;
+    let implicit = match self.peek()? {
+      Token::Block(_) | Token::Label(_) => false,
+      Token::Id(name) => ext.get(name).is_some(),
+      _ => true,
+    };
+
+    let blocks = if implicit {
       let line_start = self.cur_line;
-      let mut map = FxHashMap::default();
+      let mut map = Blocks::default();
       let (lines, branch) = self.block_inner()?;
-      map.insert(BlockID(0), BasicBlock { id: BlockID(0), preds: vec![], lines, branch, line_start });
+      map.insert(BlockID(0), BasicBlock { id: BlockID(0), preds: vec![], lines, branch, line_start, comments: vec![] });
       map
     
     } else {
       self.blocks()?
     };
 
-    Ok(Func { name, params, blocks, line_start, count: None })
+    let labels = self.labels.drain().map(|(label, bid)| (bid, label)).collect();
+    Ok(Func { name, params, blocks, line_start, count: None, comments: vec![], labels })
   }
 
   fn asm(&mut self) -> ParseResult<ASM> {  
-    let mut funcs = FxHashMap::default();
+    let mut funcs = ASM::default();
     self.skip_opt_newlines();
 
     while !matches!(self.peek(), Err(ParseError(ParseErrorKind::EOF, _))) {
@@ -361,9 +432,60 @@ impl<'a> Parser<'a> {
   }
 }
 
+/// Attach each comment to the first item on or after its line
+/// Comments after the last item of the file are attached to that item
+fn attach_comments(abs: &mut ASM, comments: Vec<(u64, String)>) {
+  // Items on the Same Line Prefer the Innermost (an Implicit Block Shares its First Line)
+  let mut items: Vec<(u64, u8, &mut Vec<String>)> = vec![];
+  for func in abs.values_mut() {
+    items.push((func.line_start, 0, &mut func.comments));
+    for block in func.blocks.values_mut() {
+      items.push((block.line_start, 1, &mut block.comments));
+      for instr in block.lines.iter_mut() {
+        items.push((instr.line, 2, &mut instr.comments));
+      }
+      items.push((block.branch.line, 2, &mut block.branch.comments));
+    }
+  }
+
+  if items.is_empty() {
+    return;
+  }
+
+  items.sort_by_key(|(line, depth, _)| (*line, std::cmp::Reverse(*depth)));
+  for (line, text) in comments {
+    let idx = items.partition_point(|(item_line, _, _)| *item_line < line).min(items.len() - 1);
+    items[idx].2.push(text);
+  }
+}
+
+/// Give each instruction and branch the location of the last `.loc` directive above it in its function
+fn attach_locs(abs: &mut ASM, locs: Vec<(u64, SrcLoc)>) {
+  for func in abs.values_mut() {
+    let start = func.line_start;
+    let loc_before = |line: u64| match locs.partition_point(|(loc_line, _)| *loc_line < line) {
+      0 => None,
+      idx => Some(&locs[idx - 1]).filter(|(loc_line, _)| *loc_line > start).map(|(_, loc)| loc.clone()),
+    };
+
+    for block in func.blocks.values_mut() {
+      for instr in block.lines.iter_mut() {
+        instr.loc = loc_before(instr.line);
+      }
+      block.branch.loc = loc_before(block.branch.line);
+    }
+  }
+}
+
 // Parses the file string into an ASM
-pub fn parse(file_str: &str) -> ParseResult<ASM> {
+pub fn parse(file_str: &str, ext: &Extensions) -> ParseResult<ASM> {
   let lexer = Token::lexer(file_str);
-  let mut parser = Parser { peeked: None, lexer, cur_line: 1 };
-  parser.asm()
+  let mut parser = Parser {
+    peeked: None, lexer, cur_line: 1, comments: vec![], locs: vec![], labels: FxHashMap::default(), ext
+  };
+  let mut abs = parser.asm()?;
+
+  attach_comments(&mut abs, parser.comments);
+  attach_locs(&mut abs, parser.locs);
+  Ok(abs)
 }
@@ -0,0 +1,230 @@
+use std::fmt::{Error, Write};
+
+use itertools::Itertools;
+
+use crate::asm::ASM;
+use crate::asm::blocks::{BasicBlock, BlockID, BranchKind, Cond, Func};
+use crate::asm::instr::{Instr, InstrKind, Operand, SrcLoc};
+use crate::ops::UnOp;
+
+
+/// Spelling of Constants in the Printed Program
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Radix {
+  Dec,
+  Hex,
+}
+
+/// Canonical Printer for Abstract Assembly
+/// Unlike the `Display` impls, the output follows `FORMAT.md` exactly and
+/// can be parsed back into the same program. Functions and blocks are
+/// printed in source order so the output is the same on every run.
+/// Comments are printed on their own lines above the item they are attached to,
+/// and `.loc` directives only where the source location changes.
+pub struct Printer<W: Write> {
+  out: W,
+  radix: Radix,
+  loc: Option<SrcLoc>,
+}
+
+impl<W: Write> Printer<W> {
+  pub fn new(out: W) -> Self {
+    Self { out, radix: Radix::Dec, loc: None }
+  }
+
+  pub fn with_radix(out: W, radix: Radix) -> Self {
+    Self { out, radix, loc: None }
+  }
+
+  fn operand(&mut self, op: &Operand) -> Result<(), Error> {
+    match (op, self.radix) {
+      (Operand::Const(val), Radix::Hex) => write!(self.out, "0x{:X}", *val as u32),
+      _ => write!(self.out, "{}", op),
+    }
+  }
+
+  fn comments(&mut self, comments: &[String], indent: &str) -> Result<(), Error> {
+    for comment in comments {
+      writeln!(self.out, "{}//{}", indent, comment)?;
+    }
+
+    Ok(())
+  }
+
+  fn operands<'a>(&mut self, ops: impl IntoIterator<Item = &'a Operand>) -> Result<(), Error> {
+    for op in ops {
+      write!(self.out, " ")?;
+      self.operand(op)?;
+    }
+
+    Ok(())
+  }
+
+  fn loc(&mut self, loc: &Option<SrcLoc>, indent: &str) -> Result<(), Error> {
+    if *loc != self.loc {
+      if let Some(loc) = loc {
+        writeln!(self.out, "{}.loc {}", indent, loc)?;
+      }
+      self.loc = loc.clone();
+    }
+
+    Ok(())
+  }
+
+  /// Instruction without its comments, with blocks named as in `func`
+  pub fn instr(&mut self, func: &Func, instr: &Instr) -> Result<(), Error> {
+    match &instr.kind {
+      InstrKind::BinOp { op, dest, src1, src2 } => {
+        write!(self.out, "{} = ", dest)?;
+        self.operand(src1)?;
+        write!(self.out, " {} ", op)?;
+        self.operand(src2)
+      },
+
+      // `-5` Would be Lexed as a Single Constant
+      InstrKind::UnOp { op: UnOp::Neg, dest, src: src @ Operand::Const(_) } => {
+        write!(self.out, "{} = {} ", dest, UnOp::Neg)?;
+        self.operand(src)
+      },
+
+      InstrKind::UnOp { op, dest, src } => {
+        write!(self.out, "{} = {}", dest, op)?;
+        self.operand(src)
+      },
+
+      InstrKind::Mov { dest, src } => {
+        write!(self.out, "{} = ", dest)?;
+        self.operand(src)
+      },
+
+      InstrKind::If { cond, block } => {
+        write!(self.out, "if ")?;
+        self.operand(cond)?;
+        write!(self.out, " {}", func.label(*block))
+      },
+
+      InstrKind::Phi { dest, srcs } => {
+        write!(self.out, "{} = phi", dest)?;
+        self.operands(srcs)
+      },
+
+      InstrKind::Call { name, dest, src } => {
+        if let Some(dest) = dest {
+          write!(self.out, "{} = ", dest)?;
+        }
+
+        write!(self.out, "call {}", name)?;
+        self.operands(src)
+      },
+
+      InstrKind::Print { value } => {
+        write!(self.out, "print ")?;
+        self.operand(value)
+      },
+
+      InstrKind::Dump => write!(self.out, "dump"),
+      InstrKind::Nop => write!(self.out, "nop"),
+
+      InstrKind::Ext { ext, dest, srcs } => {
+        if let Some(dest) = dest {
+          write!(self.out, "{} = ", dest)?;
+        }
+
+        write!(self.out, "{}", ext.mnemonic())?;
+        self.operands(srcs)
+      },
+    }
+  }
+
+  /// Branch without its comments, with blocks named as in `func`
+  pub fn branch(&mut self, func: &Func, kind: &BranchKind) -> Result<(), Error> {
+    match kind {
+      BranchKind::Cond(cond, true_block, false_block) => {
+        write!(self.out, "cmp ")?;
+        match cond {
+          Cond::BinOp(lop, op, rop) => {
+            self.operand(lop)?;
+            write!(self.out, " {} ", op)?;
+            self.operand(rop)?;
+          },
+          Cond::Value(op) => self.operand(op)?,
+        }
+
+        write!(self.out, " {} {}", func.label(*true_block), func.label(*false_block))
+      },
+
+      BranchKind::Jump(block) => write!(self.out, "jmp {}", func.label(*block)),
+      BranchKind::Ret(None) => write!(self.out, "ret"),
+      BranchKind::Ret(Some(op)) => {
+        write!(self.out, "ret ")?;
+        self.operand(op)
+      },
+    }
+  }
+
+  fn block_body(&mut self, func: &Func, block: &BasicBlock, indent: &str) -> Result<(), Error> {
+    for instr in block.lines.iter() {
+      self.comments(&instr.comments, indent)?;
+      self.loc(&instr.loc, indent)?;
+      write!(self.out, "{}", indent)?;
+      self.instr(func, instr)?;
+      writeln!(self.out)?;
+    }
+
+    self.comments(&block.branch.comments, indent)?;
+    self.loc(&block.branch.loc, indent)?;
+    write!(self.out, "{}", indent)?;
+    self.branch(func, &block.branch.kind)?;
+    writeln!(self.out)
+  }
+
+  pub fn func(&mut self, func: &Func) -> Result<(), Error> {
+    self.comments(&func.comments, "")?;
+    self.loc = None;
+    write!(self.out, "{}", func.name)?;
+    for param in func.params.iter() {
+      write!(self.out, " {}", param)?;
+    }
+    writeln!(self.out)?;
+
+    // Functions with a Single Block can Leave Out its Label
+    if let [block] = func.blocks.values().collect_vec().as_slice() {
+      if block.id == BlockID(0) && block.preds.is_empty() {
+        self.comments(&block.comments, "  ")?;
+        return self.block_body(func, block, "  ");
+      }
+    }
+
+    let blocks = func.blocks.values();
+    for (idx, block) in blocks.enumerate() {
+      if idx > 0 {
+        writeln!(self.out)?;
+      }
+
+      self.comments(&block.comments, "  ")?;
+      write!(self.out, "  {}", func.label(block.id))?;
+      for pred in block.preds.iter() {
+        write!(self.out, " {}", func.label(*pred))?;
+      }
+      writeln!(self.out)?;
+
+      self.block_body(func, block, "    ")?;
+    }
+
+    Ok(())
+  }
+
+  pub fn asm(&mut self, abs: &ASM) -> Result<(), Error> {
+    let funcs = abs.values();
+    for (idx, func) in funcs.enumerate() {
+      if idx > 0 {
+        writeln!(self.out)?;
+      }
+
+      self.func(func)?;
+    }
+
+    Ok(())
+  }
+}
+
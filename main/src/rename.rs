@@ -1,7 +1,7 @@
 use fxhash::FxHashMap;
 
 use crate::asm::ASM;
-use crate::asm::blocks::{BasicBlock, BlockID, Branch, BranchKind, Func, Cond};
+use crate::asm::blocks::{BasicBlock, Blocks, Branch, BranchKind, Func, Cond};
 use crate::asm::instr::{Instr, InstrKind, Operand, Temp, TempID};
 
 struct Context {
@@ -38,7 +38,7 @@ impl Context {
     }
   }
 
-  fn map_dests(&mut self, params: &Vec<Temp>, blocks: &FxHashMap<BlockID, BasicBlock>) {
+  fn map_dests(&mut self, params: &Vec<Temp>, blocks: &Blocks) {
     for param in params {
       self.map_temp(param);
     }
@@ -52,12 +52,12 @@ impl Context {
     }
   }
 
-  fn rename_temps(&self, params: Vec<Temp>, blocks: FxHashMap<BlockID, BasicBlock>) -> (Vec<Temp>, FxHashMap<BlockID, BasicBlock>) {
+  fn rename_temps(&self, params: Vec<Temp>, blocks: Blocks) -> (Vec<Temp>, Blocks) {
     (
       params.into_iter().map(|param| self.get_map(param)).collect(),
-      blocks.into_iter().map(|(bidx, BasicBlock { id, preds, lines, branch, line_start })| {
-        (bidx, BasicBlock { id, preds, line_start,
-          lines: lines.into_iter().map(|Instr { line, kind }| Instr { line, kind: match kind {
+      blocks.into_iter().map(|(bidx, BasicBlock { id, preds, lines, branch, line_start, comments })| {
+        (bidx, BasicBlock { id, preds, line_start, comments,
+          lines: lines.into_iter().map(|Instr { line, kind, comments, loc }| Instr { line, comments, loc, kind: match kind {
             InstrKind::BinOp { dest, op, src1, src2 } => InstrKind::BinOp {
               dest: self.get_map(dest),
               op,
@@ -96,11 +96,17 @@ impl Context {
             InstrKind::Dump => InstrKind::Dump,
             InstrKind::Nop => InstrKind::Nop,
 
+            InstrKind::Ext { ext, dest, srcs } => InstrKind::Ext {
+              ext,
+              dest: dest.map(|dest| self.get_map(dest)),
+              srcs: srcs.into_iter().map(|src| self.get_map_op(src)).collect(),
+            },
+
           }}).collect(),
 
           branch: {
-            let Branch { line, kind } = branch;
-            Branch { line, kind: match kind {
+            let Branch { line, kind, comments, loc } = branch;
+            Branch { line, comments, loc, kind: match kind {
               BranchKind::Jump(bidx) => BranchKind::Jump(bidx),
               BranchKind::Ret(src) => BranchKind::Ret(src.map(|src| self.get_map_op(src))),
               BranchKind::Cond(cond, bidx1, bidx2) => BranchKind::Cond(
@@ -122,12 +128,12 @@ impl Context {
 }
 
 pub fn rename(abs: ASM) -> ASM {
-  abs.into_iter().map(|(fname, Func { name, params, blocks, line_start, .. })| {
+  abs.into_iter().map(|(fname, Func { name, params, blocks, line_start, comments, labels, .. })| {
     
     let mut ctx = Context::new();
     ctx.map_dests(&params, &blocks);
     let (params, blocks) = ctx.rename_temps(params, blocks);
-    (fname, Func { name, line_start, params, blocks, count: Some(ctx.counter)})
+    (fname, Func { name, line_start, params, blocks, count: Some(ctx.counter), comments, labels })
 
   }).collect()
 }
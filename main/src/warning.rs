@@ -0,0 +1,179 @@
+use std::fmt::{Display, Formatter, Error as FmtError};
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::asm::instr::Temp;
+use crate::error::ErrorTrait;
+
+
+/// Kinds of Non-Fatal Diagnostics
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+  UnreachableBlock,
+  UnusedTemp,
+  NopSequence,
+  DeadRegWrite,
+  PredOrder,
+  DeadStore,
+}
+
+impl Lint {
+  pub const ALL: [Lint; 6] = [
+    Lint::UnreachableBlock,
+    Lint::UnusedTemp,
+    Lint::NopSequence,
+    Lint::DeadRegWrite,
+    Lint::PredOrder,
+    Lint::DeadStore,
+  ];
+
+  pub const fn name(&self) -> &'static str {
+    match self {
+      Self::UnreachableBlock => "unreachable-block",
+      Self::UnusedTemp => "unused-temp",
+      Self::NopSequence => "nop-sequence",
+      Self::DeadRegWrite => "dead-register-write",
+      Self::PredOrder => "pred-order",
+      Self::DeadStore => "dead-store",
+    }
+  }
+
+  /// Level without any flags
+  /// `pred-order` is off since loop headers usually list their back edge first
+  pub const fn default_level(&self) -> Level {
+    match self {
+      Self::PredOrder => Level::Allow,
+      _ => Level::Warn,
+    }
+  }
+}
+
+impl Display for Lint {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+    write!(f, "{}", self.name())
+  }
+}
+
+
+/// Lint Named on the Command Line
+/// `warnings` refers to every lint at once
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LintName {
+  All,
+  One(Lint),
+}
+
+impl FromStr for LintName {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    if s == "warnings" {
+      return Ok(Self::All);
+    }
+
+    Lint::ALL.iter()
+      .find(|lint| lint.name() == s)
+      .map(|lint| Self::One(*lint))
+      .ok_or_else(|| format!("Unknown lint `{}`", s))
+  }
+}
+
+
+/// What to Do When a Lint Fires
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+  Allow,
+  Warn,
+  Deny,
+}
+
+
+pub enum Warning {
+  UnreachableBlock(String, String, u64),
+  UnusedTemp(Temp, u64),
+  NopSequence(u64, u64),
+  DeadRegWrite(Temp, u64),
+  PredOrder(String, u64),
+  DeadStore(Temp, u64),
+}
+
+impl Warning {
+  pub const fn lint(&self) -> Lint {
+    match self {
+      Self::UnreachableBlock(_, _, _) => Lint::UnreachableBlock,
+      Self::UnusedTemp(_, _) => Lint::UnusedTemp,
+      Self::NopSequence(_, _) => Lint::NopSequence,
+      Self::DeadRegWrite(_, _) => Lint::DeadRegWrite,
+      Self::PredOrder(_, _) => Lint::PredOrder,
+      Self::DeadStore(_, _) => Lint::DeadStore,
+    }
+  }
+
+  pub const fn line(&self) -> u64 {
+    match self {
+      Self::UnreachableBlock(_, _, line)
+        | Self::UnusedTemp(_, line)
+        | Self::NopSequence(line, _)
+        | Self::DeadRegWrite(_, line)
+        | Self::PredOrder(_, line)
+        | Self::DeadStore(_, line) => *line,
+    }
+  }
+}
+
+impl Display for Warning {
+  fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+    write!(f, "warning[W{}]: {}", self.code(), self.message())?;
+    if let Some((label, line)) = self.line_label() {
+      write!(f, " - {} (line {})", label, line)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl ErrorTrait for Warning {
+  fn code(&self) -> u64 {
+    match self {
+      Self::UnreachableBlock(_, _, _) => 0,
+      Self::UnusedTemp(_, _) => 1,
+      Self::NopSequence(_, _) => 2,
+      Self::DeadRegWrite(_, _) => 3,
+      Self::PredOrder(_, _) => 4,
+      Self::DeadStore(_, _) => 5,
+    }
+  }
+
+  fn message(&self) -> &'static str {
+    match self {
+      Self::UnreachableBlock(_, _, _) => "Unreachable Block",
+      Self::UnusedTemp(_, _) => "Temporary is Never Used",
+      Self::NopSequence(_, _) => "Sequence of `nop` Instructions",
+      Self::DeadRegWrite(_, _) => "Register is Written but Never Read",
+      Self::PredOrder(_, _) => "Predecessors are Out of Order",
+      Self::DeadStore(_, _) => "Value is Never Read",
+    }
+  }
+
+  fn label(&self) -> Option<(String, Range<usize>)> {
+    None
+  }
+
+  fn line_label(&self) -> Option<(String, u64)> {
+    Some((match self {
+      Self::UnreachableBlock(func, block, _) =>
+        format!("block `{}` can not be reached from the entry of `{}`", block, func),
+      Self::UnusedTemp(temp, _) => format!("`{}` is defined here but never used", temp),
+      Self::NopSequence(_, 1) => "`nop` has no effect".to_string(),
+      Self::NopSequence(_, count) => format!("{} `nop` instructions in a row have no effect", count),
+      Self::DeadRegWrite(temp, _) => format!("`{}` is written here but never read", temp),
+      Self::PredOrder(block, _) =>
+        format!("predecessors of `{}` are not listed in source order", block),
+      Self::DeadStore(temp, _) => format!("`{}` is overwritten or goes out of scope before this value is read", temp),
+    }, self.line()))
+  }
+
+  fn note(&self) -> Option<String> {
+    Some(format!("silence with `-A {}` or promote to an error with `-D {}`", self.lint(), self.lint()))
+  }
+}
@@ -0,0 +1,19 @@
+// Not Every Test File Uses Every Helper
+#![allow(dead_code)]
+
+use vm::args::Config;
+use vm::{Error, ErrorTrait, ReturnType};
+
+
+pub fn config() -> Config {
+  Config::new_defaults("test.abs".into())
+}
+
+pub fn run(src: &str) -> Result<ReturnType, Error> {
+  vm::run(&config(), src)
+}
+
+/// Tag and code of the error, such as `W1`
+pub fn error_code<T>(res: Result<T, Error>) -> Option<String> {
+  res.err().map(|err| format!("{}{}", err.tag(), err.code()))
+}
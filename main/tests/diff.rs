@@ -0,0 +1,34 @@
+mod common;
+
+use vm::{Event, Program, ReturnType};
+
+use common::config;
+
+
+const LEFT: &str = "main\n  #0 = call f 1\n  ret #0\n\nf #0\n  #1 = #0 + 1\n  ret #1\n";
+
+fn parse(src: &str) -> Program {
+  vm::check(&config(), src, &mut vec![]).ok().unwrap()
+}
+
+#[test]
+fn first_divergent_event() {
+  let right = LEFT.replace("#0 + 1", "#0 + 2");
+  let (left, right) = (parse(LEFT), parse(&right));
+
+  let diff = vm::diff(&config(), &left, &right, "");
+  assert!(!diff.is_same());
+  assert_eq!(diff.rets, (ReturnType::Return(2), ReturnType::Return(3)));
+
+  // Both Call `f` the Same Way, but it Returns Different Values
+  let (left_event, right_event) = diff.divergence.unwrap();
+  let (left_event, right_event) = (left_event.unwrap(), right_event.unwrap());
+  assert_eq!((left_event.event, left_event.line), (Event::Return("f".to_string(), 2), 7));
+  assert_eq!((right_event.event, right_event.line), (Event::Return("f".to_string(), 3), 7));
+}
+
+#[test]
+fn same_programs() {
+  let program = parse(LEFT);
+  assert!(vm::diff(&config(), &program, &program, "").is_same());
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use vm::args::Config;
+
+use common::config;
+
+
+#[test]
+fn edges_and_counts() {
+  let src = "main\n  @0\n    #0 = 1\n    if #0 @3\n    cmp #0 == 2 @1 @2\n\n  @1  @0\n    ret 0\n\n  @2  @0\n    ret 1\n\n  @3  @0\n    ret 3\n";
+  let program = vm::check(&config(), src, &mut vec![]).ok().unwrap();
+
+  let graphs = vm::cfg_dot(&config(), &program, "");
+  assert_eq!(graphs.len(), 1);
+  let (name, graph) = &graphs[0];
+  assert_eq!(name, "main");
+  assert!(graph.contains(r#""@0" -> "@3" [label="true", style=dashed];"#), "{}", graph);
+  assert!(graph.contains(r#""@0" -> "@1" [label="true"];"#), "{}", graph);
+  assert!(graph.contains(r#""@0" -> "@2" [label="false"];"#), "{}", graph);
+  assert!(!graph.contains("fillcolor"), "{}", graph);
+
+  // Blocks that Ran are Colored by their Count, the Others are Grey
+  let graphs = vm::cfg_dot(&Config { dot_counts: true, ..config() }, &program, "");
+  let graph = &graphs[0].1;
+  assert!(graph.contains(r#""@0" [label="@0 (x1)\l"#), "{}", graph);
+  assert!(graph.contains(r#"fillcolor="0.000 1.000 1.000"];"#), "{}", graph);
+  assert!(graph.contains(r##""@1" [label="@1 (x0)\lret 0\l", style=filled, fillcolor="#dddddd"];"##), "{}", graph);
+}
@@ -0,0 +1,22 @@
+mod common;
+
+use vm::ReturnType;
+
+use common::{error_code, run};
+
+
+#[test]
+fn first_block_is_entry() {
+  // `@0` Would Return the Undefined `#0` if it Were the Entry
+  let src = "main\n  @5\n    #0 = 5\n    jmp @0\n\n  @0  @5\n    ret #0\n";
+  assert_eq!(run(src).ok(), Some(ReturnType::Return(5)));
+}
+
+#[test]
+fn entry_is_checked() {
+  let src = "main\n  @1  @0\n    ret 1\n\n  @0\n    jmp @1\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("S4"));
+
+  let src = "main\n  @0\n    #0 = phi\n    ret #0\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("S5"));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use vm::args::Config;
+use vm::ext::{InstrExt, Shape};
+use vm::ReturnType;
+
+use common::{error_code, run};
+
+
+/// `#t = sat_add a b`, Adding Without Overflowing
+struct SatAdd;
+
+impl InstrExt for SatAdd {
+  fn mnemonic(&self) -> &'static str {
+    "sat_add"
+  }
+
+  fn shape(&self) -> Shape {
+    Shape { dest: true, srcs: Some(2) }
+  }
+
+  fn exec(&self, srcs: &[i32]) -> Result<i32, ReturnType> {
+    Ok(srcs[0].saturating_add(srcs[1]))
+  }
+}
+
+/// `check_nonzero a`, Stopping with `div-by-zero` when `a` is 0
+struct CheckNonzero;
+
+impl InstrExt for CheckNonzero {
+  fn mnemonic(&self) -> &'static str {
+    "check_nonzero"
+  }
+
+  fn shape(&self) -> Shape {
+    Shape { dest: false, srcs: Some(1) }
+  }
+
+  fn check(&self, consts: &[Option<i32>]) -> Result<(), String> {
+    match consts {
+      [Some(0)] => Err("always fails on the constant 0".to_string()),
+      _ => Ok(()),
+    }
+  }
+
+  fn exec(&self, srcs: &[i32]) -> Result<i32, ReturnType> {
+    if srcs[0] == 0 { Err(ReturnType::DivByZero) } else { Ok(0) }
+  }
+}
+
+fn config() -> Config {
+  let mut config = common::config();
+  config.extensions.register(SatAdd);
+  config.extensions.register(CheckNonzero);
+  config
+}
+
+
+#[test]
+fn ext_instrs_execute() {
+  let src = "main\n  #0 = sat_add 2147483647 1\n  check_nonzero #0\n  ret #0\n";
+  assert_eq!(vm::run(&config(), src).ok(), Some(ReturnType::Return(i32::MAX)));
+
+  let src = "main\n  #0 = sat_add 0 0\n  check_nonzero #0\n  ret #0\n";
+  assert_eq!(vm::run(&config(), src).ok(), Some(ReturnType::DivByZero));
+}
+
+#[test]
+fn ext_instrs_are_checked() {
+  // Unregistered Mnemonic
+  let src = "main\n  #0 = sat_add 1 2\n  ret #0\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("P0"));
+
+  // Wrong Operand Shape
+  let src = "main\n  #0 = sat_add 1\n  ret #0\n";
+  assert_eq!(error_code(vm::run(&config(), src)).as_deref(), Some("P3"));
+  let src = "main\n  #0 = check_nonzero 1\n  ret #0\n";
+  assert_eq!(error_code(vm::run(&config(), src)).as_deref(), Some("P3"));
+
+  // Semantic Check
+  let src = "main\n  check_nonzero 0\n  ret 0\n";
+  assert_eq!(error_code(vm::run(&config(), src)).as_deref(), Some("S6"));
+}
+
+#[test]
+fn ext_instrs_roundtrip() {
+  let src = "main\n  #0 = sat_add 2147483647 1\n  check_nonzero #0\n  ret #0\n";
+  let config = config();
+  let program = vm::parse_with(&config.extensions, src).ok().unwrap();
+  let printed = program.to_string();
+  assert_eq!(vm::parse_with(&config.extensions, &printed).ok(), Some(program));
+  assert_eq!(vm::run(&config, &printed).ok(), Some(ReturnType::Return(i32::MAX)));
+
+  // Without the Extensions, the Program Does Not Parse
+  assert_eq!(error_code(vm::parse(src)).as_deref(), Some("P0"));
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use vm::ErrorTrait;
+
+use common::{config, run};
+
+
+/// `@other` is Unreachable, and Listed as the Only Predecessor of `@loop`
+const SRC: &str = "main\n  @start\n    jmp @loop\n\n  @other\n    jmp @loop\n\n  @loop  @other\n    #0 = phi 0\n    ret #0\n";
+
+#[test]
+fn labels_in_warnings() {
+  let mut warnings = vec![];
+  vm::check(&config(), SRC, &mut warnings).ok().unwrap();
+  let labels = warnings.iter().filter_map(|warning| warning.line_label()).collect::<Vec<_>>();
+  assert_eq!(labels, [("block `@other` can not be reached from the entry of `main`".to_string(), 5)]);
+}
+
+#[test]
+fn labels_in_liveness() {
+  let report = vm::liveness_report(&config(), SRC, &mut vec![]).ok().unwrap();
+  let blocks = report.lines().filter(|line| line.starts_with("  @")).collect::<Vec<_>>();
+  assert_eq!(blocks, ["  @start", "  @other", "  @loop"]);
+}
+
+#[test]
+#[should_panic(expected = "Block `@start` is Not a Predecessor of `@loop`")]
+fn labels_in_runtime_panics() {
+  let _ = run(SRC);
+}
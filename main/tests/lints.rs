@@ -0,0 +1,65 @@
+mod common;
+
+use vm::args::Config;
+use vm::warning::{Level, Lint, LintName};
+use vm::ErrorTrait;
+
+use common::{config, error_code};
+
+
+const LINTED: &str = "main\n  @0\n    #0 = 1\n    nop\n    nop\n    #eax = 2\n    ret 0\n\n  @1\n    ret 1\n";
+const LOOP: &str = "main\n  @0\n    jmp @1\n\n  @1  @2 @0\n    #0 = phi #1 0\n    cmp #0 < 3 @2 @3\n\n  @2  @1\n    #1 = #0 + 1\n    jmp @1\n\n  @3  @1\n    ret #0\n";
+
+/// Codes and lines of the warnings of the program
+fn warnings(config: &Config, src: &str) -> Vec<(String, u64)> {
+  let mut warnings = vec![];
+  vm::check(config, src, &mut warnings).ok().unwrap();
+  warnings.iter().map(|warning| (format!("W{}", warning.code()), warning.line())).collect()
+}
+
+fn levels(allow: &[LintName], warn: &[LintName], deny: &[LintName]) -> Config {
+  Config { allow: allow.to_vec(), warn: warn.to_vec(), deny: deny.to_vec(), ..config() }
+}
+
+
+#[test]
+fn lints_fire() {
+  let expected = [("W1".to_string(), 3), ("W2".to_string(), 4), ("W3".to_string(), 6), ("W0".to_string(), 9)];
+  assert_eq!(warnings(&config(), LINTED), expected);
+}
+
+#[test]
+fn pred_order_is_opt_in() {
+  assert!(warnings(&config(), LOOP).is_empty());
+  assert!(warnings(&levels(&[], &[LintName::All], &[]), LOOP).is_empty());
+  assert_eq!(warnings(&levels(&[], &[LintName::One(Lint::PredOrder)], &[]), LOOP), [("W4".to_string(), 5)]);
+
+  // Without Phis, the Order of the Predecessors Does Not Matter
+  let src = "main\n  @0\n    #0 = 0\n    jmp @1\n\n  @1  @2 @0\n    cmp #0 < 3 @2 @3\n\n  @2  @1\n    #0 = #0 + 1\n    jmp @1\n\n  @3  @1\n    ret #0\n";
+  assert!(warnings(&levels(&[], &[LintName::One(Lint::PredOrder)], &[]), src).is_empty());
+}
+
+#[test]
+fn lint_levels() {
+  let unused = LintName::One(Lint::UnusedTemp);
+
+  // Naming a Lint Beats `warnings`
+  let config = levels(&[LintName::All], &[], &[unused]);
+  assert_eq!(config.lint_level(Lint::UnusedTemp), Level::Deny);
+  assert_eq!(config.lint_level(Lint::NopSequence), Level::Allow);
+  assert_eq!(error_code(vm::check(&config, LINTED, &mut vec![])).as_deref(), Some("W1"));
+
+  let config = levels(&[unused], &[], &[LintName::All]);
+  assert_eq!(config.lint_level(Lint::UnusedTemp), Level::Allow);
+  assert_eq!(error_code(vm::check(&config, LINTED, &mut vec![])).as_deref(), Some("W2"));
+
+  // Ties Go to the Stricter Level
+  let config = levels(&[unused], &[unused], &[unused]);
+  assert_eq!(config.lint_level(Lint::UnusedTemp), Level::Deny);
+  let config = levels(&[unused], &[unused], &[]);
+  assert_eq!(config.lint_level(Lint::UnusedTemp), Level::Warn);
+
+  // Allowed Lints are Not Reported
+  let config = levels(&[LintName::All], &[], &[]);
+  assert!(warnings(&config, LINTED).is_empty());
+}
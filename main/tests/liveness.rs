@@ -0,0 +1,36 @@
+mod common;
+
+use vm::warning::Lint;
+
+use common::config;
+
+
+#[test]
+fn phi_sources_are_live_out_of_their_pred() {
+  let src = "main\n  @0\n    jmp @1\n\n  @1  @0 @2\n    #0 = phi 0 #1\n    cmp #0 < 5 @2 @3\n\n  @2  @1\n    #1 = #0 + 1\n    jmp @1\n\n  @3  @1\n    ret #0\n";
+  let report = vm::liveness_report(&config(), src, &mut vec![]).ok().unwrap();
+
+  // `#1` Flows Back Along the Edge from @2, but is Not Live into the Loop Header
+  let expected = [
+    "main",
+    "  @0", "    in:  ", "    out: ",
+    "  @1", "    in:  ", "    out: #0",
+    "  @2", "    in:  #0", "    out: #1",
+    "  @3", "    in:  #0", "    out: ",
+  ];
+  assert_eq!(report.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn dead_stores_are_reported() {
+  let src = "main\n  #0 = 1\n  #0 = 2\n  ret #0\n";
+  let mut warnings = vec![];
+  assert!(vm::check(&config(), src, &mut warnings).is_ok());
+  assert_eq!(warnings.iter().map(|warning| (warning.lint(), warning.line())).collect::<Vec<_>>(), [(Lint::DeadStore, 2)]);
+
+  // Temps that are Never Read are Unused Instead
+  let src = "main\n  #0 = 1\n  ret 0\n";
+  let mut warnings = vec![];
+  assert!(vm::check(&config(), src, &mut warnings).is_ok());
+  assert_eq!(warnings.iter().map(|warning| warning.lint()).collect::<Vec<_>>(), [Lint::UnusedTemp]);
+}
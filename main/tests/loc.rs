@@ -0,0 +1,32 @@
+mod common;
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use vm::ReturnType;
+
+use common::config;
+
+
+const SRC: &str = "main\n  #0 = 0\n  .loc foo.c0:3:5\n  #1 = 1 / #0\n  ret #1\n";
+
+#[test]
+fn runtime_errors_have_locs() {
+  let outcome = vm::run_with_input(&config(), SRC, "").ok().unwrap();
+  assert_eq!(outcome.ret, ReturnType::DivByZero);
+
+  let site = outcome.site.unwrap();
+  assert_eq!(site.line, 4);
+  assert_eq!(site.loc.map(|loc| loc.to_string()).as_deref(), Some("foo.c0:3:5"));
+}
+
+#[test]
+fn runtime_errors_note_locs() {
+  let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join("loc.abs");
+  fs::write(&path, SRC).unwrap();
+
+  let output = Command::new(env!("CARGO_BIN_EXE_main")).arg(&path).output().unwrap();
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  assert!(stderr.contains("in the C0 source at foo.c0:3:5"), "{}", stderr);
+}
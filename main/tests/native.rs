@@ -0,0 +1,46 @@
+mod common;
+
+use vm::ReturnType;
+
+use common::{config, error_code, run};
+
+
+#[test]
+fn natives_are_called() {
+  let src = "main\n  #0 = call abs -7\n  #1 = call min #0 3\n  #2 = call max #1 -1\n  ret #2\n";
+  assert_eq!(run(src).ok(), Some(ReturnType::Return(3)));
+
+  // Functions of the Program Shadow Native Ones
+  let src = "main\n  #0 = call abs -7\n  ret #0\nabs #0\n@0\n  ret 1\n";
+  assert_eq!(run(src).ok(), Some(ReturnType::Return(1)));
+}
+
+#[test]
+fn calls_are_checked() {
+  let src = "main\n  call foo 1\n  ret 0\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("S7"));
+
+  let src = "main\n  #0 = call max 1\n  ret #0\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("S8"));
+
+  let src = "main\n  #0 = call f 1 2\n  ret #0\nf #0\n@0\n  ret #0\n";
+  assert_eq!(error_code(run(src)).as_deref(), Some("S8"));
+}
+
+#[test]
+fn io_is_captured() {
+  let config = config();
+  let src = "main\n  #0 = call readint\n  #1 = call readline\n  call print #0 #1\n  call println\n  ret 0\n";
+  let outcome = vm::run_with_input(&config, src, "3\n4\n").ok().unwrap();
+  assert_eq!(outcome.ret, ReturnType::Return(0));
+  assert_eq!(outcome.output, "3 4\n");
+
+  // Programs Compared with `diff` Read the Same Input
+  let program = vm::parse("main\n  #0 = call readint\n  ret #0\n").ok().unwrap();
+  assert_eq!(vm::diff(&config, &program, &program, "3\n").rets, (ReturnType::Return(3), ReturnType::Return(3)));
+
+  // Running Out of Input Aborts
+  let outcome = vm::run_with_input(&config, src, "3\n").ok().unwrap();
+  assert_eq!(outcome.ret, ReturnType::Abort);
+  assert_eq!(outcome.output, "");
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use vm::ErrorTrait;
+
+use common::config;
+
+
+const PRE: &str = "main\n  #0 = 3\n  #1 = #0\n  #2 = 4\n  #3 = #1 + #2\n  #4 = #3 + #0\n  ret #4\n";
+
+fn validate(post: &str, map: Option<&str>) -> Vec<String> {
+  let parse = |src| vm::check(&config(), src, &mut vec![]).ok().unwrap();
+  let (pre, post) = (parse(PRE), parse(post));
+  vm::validate_regalloc(&config(), &pre, &post, map, "").iter().map(|err| format!("A{}", err.code())).collect()
+}
+
+
+#[test]
+fn valid_allocation() {
+  // `#0` and `#1` Share `#eax` Since `#1` is a Move of `#0`
+  let post = "main\n  #eax = 3\n  #eax = #eax\n  #ebx = 4\n  #ecx = #eax + #ebx\n  #eax = #ecx + #eax\n  ret #eax\n";
+  assert!(validate(post, None).is_empty());
+}
+
+#[test]
+fn interfering_allocation() {
+  // `#0` is Still Live when `#2` Overwrites `#ebx`
+  let map = "main\n  #0 #ebx\n  #1 #eax\n  #2 #ebx\n  #3 #ecx\n  #4 #eax\n";
+  assert_eq!(validate(PRE, Some(map)), ["A3"]);
+
+  // Inferred from the Allocated Program, which Computes the Wrong Result Too
+  let post = "main\n  #ebx = 3\n  #eax = #ebx\n  #ebx = 4\n  #ecx = #eax + #ebx\n  #eax = #ecx + #ebx\n  ret #eax\n";
+  assert_eq!(validate(post, None), ["A3", "A4"]);
+}
+
+#[test]
+fn wrong_result() {
+  let post = "main\n  #eax = 3\n  #eax = #eax\n  #ebx = 5\n  #ecx = #eax + #ebx\n  #eax = #ecx + #eax\n  ret #eax\n";
+  assert_eq!(validate(post, None), ["A4"]);
+}
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::Path;
+
+
+/// Text of all comments in the file, in order
+fn comments(source: &str) -> Vec<&str> {
+  source.lines().filter_map(|line| line.split_once("//")).map(|(_, text)| text.trim_end()).collect()
+}
+
+/// Check that `parse(print(parse(x))) == parse(x)`, that printing is idempotent
+/// and keeps every comment, and that hexadecimal constants parse back the same
+fn assert_roundtrip(source: &str) {
+  let program = vm::parse(source).unwrap_or_else(|err| panic!("{}\n{}", err, source));
+  let printed = program.to_string();
+  let reparsed = vm::parse(&printed).unwrap_or_else(|err| panic!("{}\n{}", err, printed));
+
+  assert_eq!(program, reparsed, "\n{}", source);
+  assert_eq!(printed, reparsed.to_string());
+  assert_eq!(comments(source), comments(&printed), "\n{}", source);
+
+  let hex = program.format(vm::Radix::Hex);
+  assert_eq!(vm::parse(&hex).ok(), Some(program), "\n{}", hex);
+}
+
+fn abs_files(dir: &Path, files: &mut Vec<String>) {
+  for entry in fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      abs_files(&path, files);
+    } else if path.extension().is_some_and(|ext| ext == "abs") {
+      files.push(fs::read_to_string(path).unwrap());
+    }
+  }
+}
+
+#[test]
+fn examples_roundtrip() {
+  let mut files = vec![];
+  abs_files(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples"), &mut files);
+
+  assert!(!files.is_empty());
+  for source in files {
+    assert_roundtrip(&source);
+  }
+}
+
+
+/// Small Deterministic Random Number Generator (xorshift64)
+struct Rng(u64);
+
+impl Rng {
+  fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  fn below(&mut self, n: usize) -> usize {
+    (self.next() % n as u64) as usize
+  }
+
+  fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+    items[self.below(items.len())]
+  }
+}
+
+const BINOPS: [&str; 19] = [
+  "+", "-", "*", "/", "%", "<<", ">>", ">>>", "==", "!=", "<", "<=", ">", ">=", "||", "&&", "|", "^", "&",
+];
+// `-` is Left Out since `-5` Lexes as a Constant
+const UNOPS: [&str; 2] = ["!", "~"];
+const SPACES: [&str; 3] = [" ", "  ", "\t"];
+
+fn temp(rng: &mut Rng) -> String {
+  match rng.below(4) {
+    0 => format!("#{}", rng.pick(&["eax", "ebx", "ecx", "edx", "edi", "esi", "r8d", "r15d"])),
+    _ => format!("#{}", rng.below(20)),
+  }
+}
+
+fn operand(rng: &mut Rng) -> String {
+  match rng.below(6) {
+    0 => format!("{}", rng.next() as i32),
+    1 => format!("0x{:X}", rng.next() as u32),
+    2 => "-2147483648".to_string(),
+    3 => format!("{}", rng.below(10)),
+    _ => temp(rng),
+  }
+}
+
+/// Blocks are Numbered, or Given Named Labels
+fn block_name(id: usize, named: bool) -> String {
+  if named { format!("@L{}.b", id) } else { format!("@{}", id) }
+}
+
+fn block(rng: &mut Rng, (num_blocks, named): (usize, bool)) -> String {
+  block_name(rng.below(num_blocks), named)
+}
+
+fn instr(rng: &mut Rng, num_blocks: (usize, bool)) -> String {
+  let sp = rng.pick(&SPACES);
+  match rng.below(11) {
+    0 => format!("{} ={}{}", temp(rng), sp, operand(rng)),
+    1 => format!("{} = {}{}", temp(rng), rng.pick(&UNOPS), operand(rng)),
+    2 => format!("{} = - {}", temp(rng), operand(rng)),
+    3 => format!("{} = -{}", temp(rng), temp(rng)),
+    4 => format!("{} = phi {} {}", temp(rng), operand(rng), operand(rng)),
+    5 => format!("{} = call f {} {}", temp(rng), operand(rng), operand(rng)),
+    6 => format!("call f{}{}", sp, operand(rng)),
+    7 => format!("if {} {}", operand(rng), block(rng, num_blocks)),
+    8 => format!("print {}", operand(rng)),
+    9 => rng.pick(&["dump", "nop"]).to_string(),
+    _ => format!("{} = {}{}{} {}", temp(rng), operand(rng), sp, rng.pick(&BINOPS), operand(rng)),
+  }
+}
+
+fn branch(rng: &mut Rng, num_blocks: (usize, bool)) -> String {
+  match rng.below(5) {
+    0 => "ret".to_string(),
+    1 => format!("ret {}", operand(rng)),
+    2 => format!("jmp {}", block(rng, num_blocks)),
+    3 => format!("cmp {} {} {}", operand(rng), block(rng, num_blocks), block(rng, num_blocks)),
+    _ => format!(
+      "cmp {} {} {} {} {}",
+      operand(rng), rng.pick(&BINOPS), operand(rng), block(rng, num_blocks), block(rng, num_blocks)
+    ),
+  }
+}
+
+/// Random program with irregular spacing, blank lines and comments
+fn program(rng: &mut Rng) -> String {
+  let mut out = String::new();
+
+  for name in ["main", "f"] {
+    out += &format!("// function {}\n{} {} {}\n", name, name, temp(rng), temp(rng));
+
+    let num_blocks = (1 + rng.below(4), rng.below(2) == 0);
+    let labeled = num_blocks.0 > 1 || rng.below(2) == 0;
+    for id in 0..num_blocks.0 {
+      if labeled {
+        let preds: Vec<_> = (0..rng.below(3)).map(|_| block(rng, num_blocks)).collect();
+        out += &format!("  {} {}\n", block_name(id, num_blocks.1), preds.join(rng.pick(&SPACES)));
+      }
+
+      for _ in 0..rng.below(6) {
+        if rng.below(4) == 0 {
+          out += &format!("  .loc\t{}.l2:{}:{}\n", name, rng.below(100), rng.below(80));
+        }
+        out += &format!("{}{}  // comment\n", rng.pick(&SPACES), instr(rng, num_blocks));
+      }
+      out += &format!("    {}\n\n", branch(rng, num_blocks));
+    }
+  }
+
+  out
+}
+
+#[test]
+fn random_programs_roundtrip() {
+  let mut rng = Rng(0x411_411_411);
+  for _ in 0..500 {
+    assert_roundtrip(&program(&mut rng));
+  }
+}
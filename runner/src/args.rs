@@ -1,8 +1,12 @@
 use std::ffi::{OsStr, OsString};
 use std::path::PathBuf;
 
+use glob::Pattern;
 use structopt::StructOpt;
 
+use crate::report::Report;
+use crate::score::Weight;
+
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Quiet {
@@ -28,7 +32,7 @@ fn dir_path(path: &OsStr) -> Result<PathBuf, OsString> {
 #[derive(Debug, StructOpt)]
 #[structopt(name="Compiler Test Runner", about="Test Runner Script for Compiler", no_version)]
 pub struct Options {
-  /// Set Level of Quiet
+  /// Set Level of Quiet (`-q` Leaves Out the Details of Each Failure)
   #[structopt(short, parse(from_occurrences=Quiet::from))]
   pub quiet: Quiet,
 
@@ -40,6 +44,78 @@ pub struct Options {
   #[structopt(short="b", long="bin", default_value="./c0c")]
   pub bin_path: PathBuf,
 
+  /// Grade the `.abs` Files of the Directory Directly with the VM, Without a Compiler
+  #[structopt(long="abs")]
+  pub abs: bool,
+
+  /// Flag Making the Compiler Emit Abstract Assembly
+  #[structopt(long="emit-arg", default_value="-eabs", allow_hyphen_values=true)]
+  pub emit_arg: String,
+
+  /// Flag Making the Compiler Only Typecheck, for `error` Tests
+  #[structopt(long="typecheck-arg", default_value="-t", allow_hyphen_values=true)]
+  pub typecheck_arg: String,
+
+  /// Name of the Emitted File Next to the Test, where `{file}` is the Test's File Name and `{stem}` is it Without the Extension
+  #[structopt(long="output-name", default_value="{file}.abs")]
+  pub output_name: String,
+
+  /// Extra Argument Passed to Every Compiler Run, such as `--compiler-arg=-O1` (Repeatable)
+  #[structopt(long="compiler-arg", number_of_values=1, allow_hyphen_values=true)]
+  pub compiler_args: Vec<String>,
+
+  /// Exit Status of the Compiler when it Rejects a Program, Anything Else Counts as an Internal Error
+  #[structopt(long="error-exit-code", default_value="1")]
+  pub error_exit_code: i32,
+
+  /// Seconds the Compiler Can Run on a Test Before it is Killed
+  #[structopt(long="compile-timeout", default_value="60")]
+  pub compile_timeout: u64,
+
+  /// Seconds the VM Can Run a Test
+  #[structopt(long="run-timeout", default_value="6")]
+  pub run_timeout: u64,
+
+  /// Only Run Tests of a Lab, such as `l3` (Repeatable)
+  #[structopt(long="lab", number_of_values=1)]
+  pub labs: Vec<String>,
+
+  /// Only Run Tests Whose Path in the Test Directory Matches a Glob, such as `l3/*loop*` (Repeatable)
+  #[structopt(long="include", number_of_values=1)]
+  pub include: Vec<Pattern>,
+
+  /// Skip Tests Whose Path in the Test Directory Matches a Glob (Repeatable)
+  #[structopt(long="exclude", number_of_values=1)]
+  pub exclude: Vec<Pattern>,
+
+  /// Points of Each Test in a Category, as `error=<points>`, `return=<points>` or `exception=<points>` (Default: 1)
+  #[structopt(long="weight", number_of_values=1)]
+  pub weights: Vec<Weight>,
+
+  /// Fraction of the Weighted Tests of a Lab that Must Pass for Full Points
+  #[structopt(long="threshold", default_value="0.9")]
+  pub threshold: f64,
+
+  /// Points Each Lab is Worth
+  #[structopt(long="lab-points", default_value="100")]
+  pub lab_points: f64,
+
+  /// Write a Report as `junit=<path>` or `json=<path>` (Repeatable)
+  #[structopt(long="report", number_of_values=1)]
+  pub reports: Vec<Report>,
+
+  /// Keep the Files the Compiler Emitted for Failing Tests (and its Stderr) Under DIR
+  #[structopt(long="keep-failures", name="DIR", parse(from_os_str))]
+  pub keep_failures: Option<PathBuf>,
+
+  /// Save the Outcome of Every Test to PATH, to Compare Later Runs Against
+  #[structopt(long="save-baseline", name="PATH", parse(from_os_str))]
+  pub save_baseline: Option<PathBuf>,
+
+  /// Compare Against a Saved Baseline, Exiting with 1 Only if a Test Stopped Passing
+  #[structopt(long="baseline", name="BASELINE", parse(from_os_str))]
+  pub baseline: Option<PathBuf>,
+
   /// Input Directory of Test Cases
   #[structopt(parse(try_from_os_str=dir_path))]
   pub inpath: PathBuf,
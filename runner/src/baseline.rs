@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::result::{Status, TestResult};
+
+
+/// Outcome of One Test in a Snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+  pub status: Status,
+  pub actual: Option<String>,
+}
+
+/// Outcome of Every Test of a Run, Keyed by the Path in the Test Directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+  pub tests: BTreeMap<String, Entry>,
+}
+
+impl Snapshot {
+  pub fn new(results: &[TestResult], inpath: &Path) -> Self {
+    let tests = results.iter().map(|result| {
+      let name = result.path.strip_prefix(inpath).unwrap_or(&result.path).display().to_string();
+      (name, Entry { status: result.graded.status, actual: result.graded.actual.clone() })
+    }).collect();
+
+    Snapshot { tests }
+  }
+
+  pub fn load(path: &Path) -> io::Result<Self> {
+    let contents = fs::read_to_string(path)?;
+    serde_json::from_str(&contents).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+  }
+
+  pub fn save(&self, path: &Path) -> io::Result<()> {
+    fs::write(path, serde_json::to_string_pretty(self).unwrap())
+  }
+}
+
+
+/// Tests Whose Outcome Changed Since the Baseline
+#[derive(Debug, Default)]
+pub struct Comparison {
+  /// Passed in the baseline but not anymore
+  pub regressions: Vec<(String, Entry, Entry)>,
+  pub fixed: Vec<(String, Entry, Entry)>,
+  /// Started or stopped timing out, without passing either time
+  pub timeouts: Vec<(String, Entry, Entry)>,
+  /// In the baseline but not run anymore
+  pub missing: Vec<(String, Entry)>,
+}
+
+impl Comparison {
+  /// Compare the tests that are in both snapshots, and list those only in the baseline
+  pub fn new(baseline: &Snapshot, current: &Snapshot) -> Self {
+    let missing = baseline.tests.iter()
+      .filter(|(name, _)| !current.tests.contains_key(*name))
+      .map(|(name, before)| (name.clone(), before.clone()))
+      .collect();
+    let mut comparison = Comparison { missing, ..Comparison::default() };
+
+    for (name, now) in current.tests.iter() {
+      let before = match baseline.tests.get(name) {
+        Some(before) if before.status != now.status => before,
+        _ => continue,
+      };
+
+      let change = (name.clone(), before.clone(), now.clone());
+      match (before.status, now.status) {
+        (Status::Pass, _) => comparison.regressions.push(change),
+        (_, Status::Pass) => comparison.fixed.push(change),
+        _ => comparison.timeouts.push(change),
+      }
+    }
+
+    comparison
+  }
+
+  /// Whether a Test Stopped Passing, or a Test that Passed is Missing
+  pub fn regressed(&self) -> bool {
+    !self.regressions.is_empty() || self.missing.iter().any(|(_, before)| before.status == Status::Pass)
+  }
+
+  pub fn print(&self) {
+    let sections = [
+      ("Regressions", &self.regressions),
+      ("Newly Passing", &self.fixed),
+      ("Changed Timeouts", &self.timeouts),
+    ];
+
+    let describe = |entry: &Entry| format!("{} ({})", entry.status, entry.actual.as_deref().unwrap_or("nothing"));
+    println!("-- Compared to Baseline --");
+    for (title, changes) in sections {
+      if changes.is_empty() {
+        continue;
+      }
+
+      println!("-- {} --", title);
+      for (name, before, now) in changes {
+        println!("  {}: {} -> {}", name, describe(before), describe(now));
+      }
+    }
+
+    if !self.missing.is_empty() {
+      println!("-- Missing --");
+      for (name, before) in self.missing.iter() {
+        println!("  {}: {}", name, describe(before));
+      }
+    }
+    println!(
+      "-- Regressions: {}, Newly Passing: {}, Changed Timeouts: {}, Missing: {} --",
+      self.regressions.len(), self.fixed.len(), self.timeouts.len(), self.missing.len()
+    );
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn snapshot(tests: &[(&str, Status)]) -> Snapshot {
+    let tests = tests.iter()
+      .map(|(name, status)| (name.to_string(), Entry { status: *status, actual: Some(status.to_string()) }))
+      .collect();
+    Snapshot { tests }
+  }
+
+  fn names(changes: &[(String, Entry, Entry)]) -> Vec<&str> {
+    changes.iter().map(|(name, _, _)| name.as_str()).collect()
+  }
+
+  #[test]
+  fn changes_are_split() {
+    let baseline = snapshot(&[
+      ("broke", Status::Pass), ("slowed", Status::Pass), ("fixed", Status::Fail), ("sped-up", Status::Timeout),
+      ("hangs", Status::Fail), ("stopped-hanging", Status::Timeout), ("same", Status::Fail),
+    ]);
+    let current = snapshot(&[
+      ("broke", Status::Fail), ("slowed", Status::Timeout), ("fixed", Status::Pass), ("sped-up", Status::Pass),
+      ("hangs", Status::Timeout), ("stopped-hanging", Status::Fail), ("same", Status::Fail),
+    ]);
+
+    let comparison = Comparison::new(&baseline, &current);
+    assert_eq!(names(&comparison.regressions), ["broke", "slowed"]);
+    assert_eq!(names(&comparison.fixed), ["fixed", "sped-up"]);
+    assert_eq!(names(&comparison.timeouts), ["hangs", "stopped-hanging"]);
+  }
+
+  #[test]
+  fn missing_tests() {
+    let baseline = snapshot(&[("removed", Status::Pass), ("kept", Status::Pass), ("failed", Status::Fail)]);
+    let current = snapshot(&[("added", Status::Fail), ("kept", Status::Pass)]);
+
+    // Added Tests are Skipped, and Only Missing Tests that Passed Count as Regressions
+    let comparison = Comparison::new(&baseline, &current);
+    assert!(comparison.regressions.is_empty() && comparison.fixed.is_empty() && comparison.timeouts.is_empty());
+    assert_eq!(comparison.missing.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), ["failed", "removed"]);
+    assert!(comparison.regressed());
+
+    let comparison = Comparison::new(&snapshot(&[("failed", Status::Fail)]), &current);
+    assert!(!comparison.missing.is_empty() && !comparison.regressed());
+  }
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+
+use crate::args::Options;
+
+
+/// Every File Below `dir`, Including Subdirectories
+pub fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+  let entries = match dir.read_dir() {
+    Ok(entries) => entries,
+    Err(_) => return,
+  };
+
+  for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+    if path.is_dir() {
+      collect_files(&path, files);
+    } else {
+      files.push(path);
+    }
+  }
+}
+
+/// Lab a Test Belongs to, from its Extension (`l3` for both `foo.l3` and `foo.l3.abs`)
+pub fn lab(path: &Path) -> Option<String> {
+  let ext = path.extension()?;
+  if ext == "abs" {
+    lab(Path::new(path.file_stem()?))
+  } else {
+    Some(ext.to_string_lossy().to_string())
+  }
+}
+
+/// Whether the File is a Test, and it is Selected by `--lab`, `--include` and `--exclude`
+/// Globs are matched against the path relative to the test directory
+pub fn is_selected(path: &Path, opt: &Options) -> bool {
+  let is_test = match path.extension() {
+    None => false,
+    Some(x) if opt.abs => x == "abs",
+    Some(x) => x != "h0" && x != "in" && x != "out",
+  };
+
+  let rel_path = path.strip_prefix(&opt.inpath).unwrap_or(path);
+  let matches = |patterns: &[Pattern]| patterns.iter().any(|pattern| pattern.matches_path(rel_path));
+
+  is_test
+    && (opt.labs.is_empty() || lab(path).is_some_and(|lab| opt.labs.iter().any(|x| x.trim_start_matches('.') == lab)))
+    && (opt.include.is_empty() || matches(&opt.include))
+    && !matches(&opt.exclude)
+}
+
+/// Header File Passed to the Compiler with `-l`, from the Directory of the Test
+/// This is `<test>.h0`, or else a header named after the lab of the test, like `15411-l3.h0`
+pub fn header_file(path: &Path) -> Option<PathBuf> {
+  let own = path.with_extension("h0");
+  if own.is_file() {
+    return Some(own);
+  }
+
+  let lab = lab(path)?;
+  path.parent()?.read_dir().ok()?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .filter(|header| header.extension().is_some_and(|ext| ext == "h0"))
+    .filter(|header| header.file_stem().is_some_and(|stem| stem.to_string_lossy().ends_with(&lab)))
+    .min()
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::fs;
+  use structopt::StructOpt;
+
+  #[test]
+  fn labs() {
+    assert_eq!(lab(Path::new("tests/add.l3")).as_deref(), Some("l3"));
+    assert_eq!(lab(Path::new("tests/add.l3.abs")).as_deref(), Some("l3"));
+    assert_eq!(lab(Path::new("tests/add.abs")), None);
+    assert_eq!(lab(Path::new("tests/add")), None);
+  }
+
+  #[test]
+  fn selection() {
+    // The Test Directory Must Exist, but the Tests Need Not
+    let dir = tempfile::tempdir().unwrap();
+    let inpath = dir.path().to_str().unwrap();
+    let selected = |args: &[&str], path: &str| {
+      let opt = Options::from_iter(["runner"].iter().chain(args).chain(&[inpath]));
+      is_selected(&dir.path().join(path), &opt)
+    };
+
+    assert!(selected(&[], "l3/add.l3"));
+    assert!(!selected(&[], "l3/add.in") && !selected(&[], "l3/15411-l3.h0") && !selected(&[], "README"));
+    assert!(selected(&["--abs"], "l3/add.l3.abs") && !selected(&["--abs"], "l3/add.l3"));
+
+    // Labs With or Without the Dot, and Globs Relative to the Test Directory
+    assert!(selected(&["--lab", ".l3", "--lab", "l4"], "l3/add.l3"));
+    assert!(!selected(&["--lab", "l4"], "l3/add.l3"));
+    assert!(selected(&["--include", "l3/*add*"], "l3/add.l3"));
+    assert!(!selected(&["--include", "l3/*add*"], "l3/sub.l3"));
+    assert!(!selected(&["--include", "l3/*", "--exclude", "*/add.*"], "l3/add.l3"));
+  }
+
+  #[test]
+  fn header_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = |name: &str| dir.path().join(name);
+    for name in ["add.l3", "sub.l3", "sub.h0", "mul.l4", "15411-l3.h0", "15411-l2.h0"] {
+      fs::write(path(name), "").unwrap();
+    }
+
+    assert_eq!(header_file(&path("sub.l3")), Some(path("sub.h0")));
+    assert_eq!(header_file(&path("add.l3")), Some(path("15411-l3.h0")));
+    assert_eq!(header_file(&path("mul.l4")), None);
+  }
+}
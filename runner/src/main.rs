@@ -1,15 +1,21 @@
 mod args;
+mod baseline;
+mod discover;
+mod report;
+mod result;
+mod score;
 
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter, Result as FmtResult};
-use std::path::PathBuf;
-use std::time::Instant;
-use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::fs::{self, File};
 use std::io::BufReader;
 use std::io::prelude::*;
-use std::process::Command;
+use std::process::{self, Command, ExitStatus, Stdio};
 use std::sync::mpsc::{Sender, Receiver};
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use itertools::Itertools;
@@ -19,17 +25,21 @@ use indicatif::{ProgressBar, ProgressStyle};
 use structopt::StructOpt;
 
 use vm::args::Config;
-use vm::{run_wrapper as run_vm, ReturnType};
+use vm::ReturnType;
 use crate::args::{Options, Quiet};
+use crate::baseline::{Comparison, Snapshot};
+use crate::discover::{collect_files, header_file, is_selected, lab};
+use crate::result::{Category, CompilerRun, Graded, Reason, Status, TestResult};
+use crate::score::{Scheme, Score};
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TestCase {
   Typecheck,
   Error,
   Return(i32),
   DivByZero,
-  // Abort,
+  Abort,
 }
 
 impl Display for TestCase {
@@ -38,12 +48,33 @@ impl Display for TestCase {
       TestCase::Error => write!(f, "error"),
       TestCase::Return(val) => write!(f, "return {}", val),
       TestCase::DivByZero => write!(f, "div-by-zero"),
+      TestCase::Abort => write!(f, "abort"),
       TestCase::Typecheck => write!(f, "typecheck"),
     }
   }
 }
 
-fn expected_res(entry: &PathBuf) -> Option<TestCase> {
+impl TestCase {
+  pub fn category(&self) -> Category {
+    match self {
+      TestCase::Error | TestCase::Typecheck => Category::Error,
+      TestCase::Return(_) => Category::Return,
+      TestCase::DivByZero | TestCase::Abort => Category::Exception,
+    }
+  }
+}
+
+/// Expected Result and Limits from the `//test` Header on the First Line of a Test
+pub struct Header {
+  pub expected: TestCase,
+  /// Overrides of `--run-timeout` and `--compile-timeout`, in Seconds
+  pub run_timeout: Option<u64>,
+  pub compile_timeout: Option<u64>,
+}
+
+/// Parse the `//test <result> [run-timeout=<secs>] [compile-timeout=<secs>]` Header of a Test
+/// Abstract assembly files can also leave out `test`, like `// return 5` in `examples/`
+fn test_header(entry: &PathBuf, abs: bool) -> Option<Header> {
   let file = File::open(entry).ok()?;
   let mut first_line = String::new();
   
@@ -52,21 +83,42 @@ fn expected_res(entry: &PathBuf) -> Option<TestCase> {
   buffer.read_line(&mut first_line).ok()?;
   first_line = first_line.trim().trim_end_matches(";").to_string();
 
-  if first_line == "//test error" {
+  let header = match first_line.strip_prefix("//test ") {
+    Some(header) => header,
+    None if abs => first_line.strip_prefix("//")?.trim_start(),
+    None => return None,
+  };
+
+  // Split Off the Timeout Overrides
+  let (options, words): (Vec<_>, Vec<_>) = header.split_whitespace().partition(|word| word.contains('='));
+  let mut run_timeout = None;
+  let mut compile_timeout = None;
+  for option in options {
+    match option.split_once('=')? {
+      ("run-timeout", secs) => run_timeout = Some(secs.parse().ok()?),
+      ("compile-timeout", secs) => compile_timeout = Some(secs.parse().ok()?),
+      _ => return None,
+    }
+  }
+
+  let expected = expected_res(&words.join(" "))?;
+  Some(Header { expected, run_timeout, compile_timeout })
+}
+
+fn expected_res(header: &str) -> Option<TestCase> {
+  if header == "error" {
     Some(TestCase::Error)
-  } else if first_line == "//test div-by-zero" {
+  } else if header == "div-by-zero" {
     Some(TestCase::DivByZero)
-  } else if first_line == "//test typecheck" {
+  } else if header == "typecheck" {
     Some(TestCase::Typecheck)
-  // } else if first_line == "//test abort" {
-  //   Some(TestCase::Abort)
-
-
-  } else if first_line.starts_with("//test return ") {
-    if first_line.chars().nth(14)? == '~' {
-      Some(TestCase::Return(-first_line[15..].parse::<i64>().ok()? as i32))
+  } else if header == "abort" {
+    Some(TestCase::Abort)
+  } else if let Some(val) = header.strip_prefix("return ") {
+    if let Some(val) = val.strip_prefix('~') {
+      Some(TestCase::Return(-val.parse::<i64>().ok()? as i32))
     } else {
-      Some(TestCase::Return(first_line[14..].parse().ok()?))
+      Some(TestCase::Return(val.parse().ok()?))
     }
   } else {
     None
@@ -78,31 +130,208 @@ enum Passing {
   Ending(usize)
 }
 
-fn exec(mut compiler: Command, path: &PathBuf, expected_ret: ReturnType) -> Option<bool> {
-  let ext = path.extension().unwrap().to_os_string();
+/// Run the Abstract Assembly File with the `.in` File of the Test as Input
+/// When the Test has an `.out` File, the Output of the Program Must Match It
+fn run_vm(abs_path: &Path, path: &Path, expected_ret: ReturnType, timeout: u64) -> Graded {
+  let abs_str = match fs::read_to_string(abs_path) {
+    Ok(abs_str) => abs_str,
+    Err(err) => return Graded::fail(Reason::VmError, "no abstract assembly")
+      .with_diagnostics(format!("Unable to Read `{}`: {}", abs_path.display(), err)),
+  };
+  let input = fs::read_to_string(path.with_extension("in")).unwrap_or_default();
+  let expected_out = fs::read_to_string(path.with_extension("out")).ok();
+
+  match vm::run_with_input(&Config::new_timeout(abs_path.to_path_buf(), timeout), &abs_str, &input) {
+    Err(err) => Graded::fail(Reason::VmError, "error").with_diagnostics(err),
+    Ok(outcome) if outcome.ret == ReturnType::Timeout => Graded::fail(Reason::Timeout, outcome.ret),
+    Ok(outcome) if outcome.ret != expected_ret => Graded::fail(Reason::WrongResult, outcome.ret),
+    Ok(outcome) if expected_out.as_ref().is_some_and(|out| *out != outcome.output) =>
+      Graded::fail(Reason::WrongOutput, outcome.ret)
+        .with_diagnostics(format!("Output Does Not Match `{}`", path.with_extension("out").display())),
+    Ok(outcome) => Graded::pass(outcome.ret),
+  }
+}
+
+/// Grade an Abstract Assembly File Directly with the VM, Without a Compiler
+fn grade_abs(path: &Path, expec: TestCase, run_timeout: u64) -> Graded {
+  let expected_ret = match expec {
+    TestCase::Return(val) => ReturnType::Return(val),
+    TestCase::DivByZero => ReturnType::DivByZero,
+    TestCase::Abort => ReturnType::Abort,
+
+    // Only Parsed and Checked
+    TestCase::Error | TestCase::Typecheck => {
+      let config = Config::new_defaults(path.to_path_buf());
+      let checked = fs::read_to_string(path).map_err(|err| format!("Unable to Read `{}`: {}", path.display(), err))
+        .and_then(|abs_str| vm::check(&config, &abs_str, &mut vec![]).map_err(|err| err.to_string()));
+
+      return match (expec, checked) {
+        (TestCase::Error, Err(err)) => Graded::pass("error").with_diagnostics(err),
+        (TestCase::Typecheck, Ok(_)) => Graded::pass("typecheck"),
+        (_, Err(err)) => Graded::fail(Reason::VmError, "error").with_diagnostics(err),
+        (_, Ok(_)) => Graded::fail(Reason::Accepted, "typecheck"),
+      };
+    },
+  };
+
+  // `foo.l1.abs` Uses the `foo.in` and `foo.out` of the Original `foo.l1` Test
+  run_vm(path, &path.with_extension(""), expected_ret, run_timeout)
+}
+
+/// Run the Compiler, Keeping its Exit Status and Stderr
+/// It is killed after `timeout` seconds, and a compiler that could not be started counts as crashed
+fn compile(compiler: &mut Command, timeout: u64) -> (bool, CompilerRun) {
+  let mut child = match compiler.stdout(Stdio::null()).stderr(Stdio::piped()).spawn() {
+    Ok(child) => child,
+    Err(err) => return (false, CompilerRun {
+      exit_code: None, signal: None, timed_out: false, stderr: format!("Unable to Run the Compiler: {}", err)
+    }),
+  };
+
+  // Read Stderr on the Side so the Compiler Never Blocks on a Full Pipe
+  let mut stderr = child.stderr.take().unwrap();
+  let stderr_buf = Arc::new(Mutex::new(vec![]));
+  let (tx, rx) = mpsc::channel();
+  let reader_buf = Arc::clone(&stderr_buf);
+  thread::spawn(move || {
+    let mut chunk = [0; 4096];
+    while let Ok(len @ 1..) = stderr.read(&mut chunk) {
+      reader_buf.lock().unwrap().extend_from_slice(&chunk[..len]);
+    }
+    let _ = tx.send(());
+  });
+
+  let deadline = Instant::now() + Duration::from_secs(timeout);
+  let status = loop {
+    match child.try_wait() {
+      Ok(Some(status)) => break Some(status),
+      Ok(None) if Instant::now() < deadline => thread::sleep(Duration::from_millis(10)),
+      _ => {
+        let _ = child.kill();
+        let _ = child.wait();
+        break None;
+      },
+    }
+  };
+
+  // Processes Started by a Killed Compiler may Still Hold Stderr Open, so Keep What was Read
+  let _ = rx.recv_timeout(Duration::from_secs(1));
+  let stderr = String::from_utf8_lossy(&stderr_buf.lock().unwrap()).to_string();
+  match status {
+    Some(status) => (status.success(), CompilerRun {
+      exit_code: status.code(), signal: exit_signal(&status), timed_out: false, stderr
+    }),
+    None => (false, CompilerRun { exit_code: None, signal: None, timed_out: true, stderr }),
+  }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: &ExitStatus) -> Option<i32> {
+  use std::os::unix::process::ExitStatusExt;
+  status.signal()
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: &ExitStatus) -> Option<i32> {
+  None
+}
+
+/// Failure of a Compiler Run that was Expected to Succeed
+/// Only the documented error status counts as rejecting the program, other failures are the compiler's fault
+fn compile_failure(compiler_run: CompilerRun, opt: &Options) -> Graded {
+  let (reason, actual) = match compiler_run.exit_code {
+    _ if compiler_run.timed_out => (Reason::CompilerTimeout, "timeout".to_string()),
+    None => (Reason::CompilerCrash, "crash".to_string()),
+    Some(code) if code == opt.error_exit_code => (Reason::CompilerRejected, "error".to_string()),
+    Some(code) => (Reason::CompilerInternalError, format!("exit status {}", code)),
+  };
+  Graded::fail(reason, actual).with_compiler(compiler_run)
+}
+
+/// Path of the File the Compiler Emits for a Test, Following `--output-name`
+fn output_path(path: &Path, output_name: &str) -> PathBuf {
+  let file = path.file_name().unwrap().to_string_lossy();
+  let stem = path.file_stem().unwrap().to_string_lossy();
+  path.with_file_name(output_name.replace("{file}", &file).replace("{stem}", &stem))
+}
 
-  let exec_success = compiler.arg("-eabs").output()
-    .expect("Failed to run compiler")
-    .status.success();
+/// Compile the Copy of the Test at `source` and Run the Emitted File
+fn exec(mut compiler: Command, source: &Path, path: &Path, expected_ret: ReturnType, header: &Header, opt: &Options) -> Graded {
+  let compile_timeout = header.compile_timeout.unwrap_or(opt.compile_timeout);
+  let (exec_success, compiler_run) = compile(compiler.arg(&opt.emit_arg), compile_timeout);
 
   if exec_success {
-    let mut abs_ext = ext.clone();
-    abs_ext.push(".abs");
-    let new_path = path.with_extension(abs_ext);
-
-    let res = match run_vm(&Config::new_timeout(new_path.clone(), 6)) {
-      Err(_) => Some(true),
-      Ok(ReturnType::Timeout) => Some(false),
-      Ok(ret) if ret == expected_ret => None,
-      _ => Some(true),
-    };
+    let new_path = output_path(source, &opt.output_name);
 
-    let _ = std::fs::remove_file(new_path);
-    res
+    let res = run_vm(&new_path, path, expected_ret, header.run_timeout.unwrap_or(opt.run_timeout));
+    res.with_compiler(compiler_run)
 
   } else {
-    Some(true)
+    compile_failure(compiler_run, opt)
+  }
+}
+
+/// Compile a Copy of the Test in its Own Temporary Directory, so Nothing is Written Next to the Test
+/// The directory is moved under `--keep-failures` when the test does not pass
+fn grade_compiled(path: &Path, header: &Header, opt: &Options) -> Graded {
+  let workspace = tempfile::tempdir().expect("Failed to create a temporary directory");
+  let source = workspace.path().join(path.file_name().unwrap());
+  fs::copy(path, &source).expect("Failed to copy the test");
+
+  let mut compiler = Command::new(opt.bin_path.as_os_str().to_str().unwrap());
+  if let Some(header_path) = header_file(path) {
+    compiler.arg("-l").arg(header_path);
+  }
+  compiler.arg(&source).args(&opt.compiler_args);
+
+  let compile_timeout = header.compile_timeout.unwrap_or(opt.compile_timeout);
+  let graded = match header.expected {
+    TestCase::Error => {
+      match compile(compiler.arg(&opt.typecheck_arg), compile_timeout) {
+        (true, compiler_run) => Graded::fail(Reason::Accepted, "typecheck").with_compiler(compiler_run),
+        (false, compiler_run) if compiler_run.exit_code == Some(opt.error_exit_code) && !compiler_run.timed_out =>
+          Graded::pass("error").with_compiler(compiler_run),
+        (false, compiler_run) => compile_failure(compiler_run, opt),
+      }
+    },
+
+    TestCase::Return(val) => exec(compiler, &source, path, ReturnType::Return(val), header, opt),
+    TestCase::DivByZero => exec(compiler, &source, path, ReturnType::DivByZero, header, opt),
+    TestCase::Abort => exec(compiler, &source, path, ReturnType::Abort, header, opt),
+
+    TestCase::Typecheck => {
+      match compile(compiler.arg(&opt.typecheck_arg), compile_timeout) {
+        (true, compiler_run) => Graded::pass("typecheck").with_compiler(compiler_run),
+        (false, compiler_run) => compile_failure(compiler_run, opt),
+      }
+    },
+  };
+
+  if let (Some(keep_dir), true) = (&opt.keep_failures, graded.status != Status::Pass) {
+    let dest = keep_dir.join(path.strip_prefix(&opt.inpath).unwrap_or(path));
+    if let Err(err) = keep_artifacts(workspace.path(), &dest, &graded) {
+      eprintln!("Unable to Keep the Artifacts of `{}` in `{}`: {}", path.display(), dest.display(), err);
+    }
+  }
+
+  graded
+}
+
+/// Copy Everything the Compiler Left in the Workspace, and its Stderr, to `dest`
+fn keep_artifacts(workspace: &Path, dest: &Path, graded: &Graded) -> std::io::Result<()> {
+  let _ = fs::remove_dir_all(dest);
+  fs::create_dir_all(dest)?;
+  for entry in fs::read_dir(workspace)? {
+    let entry = entry?;
+    if entry.file_type()?.is_file() {
+      fs::copy(entry.path(), dest.join(entry.file_name()))?;
+    }
+  }
+
+  if let Some(compiler) = &graded.compiler {
+    fs::write(dest.join("compiler.stderr"), &compiler.stderr)?;
   }
+  Ok(())
 }
 
 
@@ -113,20 +342,17 @@ fn main() {
     .stack_size(64 * 1024 * 1024)
     .build_global().unwrap();
 
-  // Collect and Sort Test Files
-  let test_files = opt.inpath.read_dir().unwrap().collect::<Vec<_>>();
-  let mut test_files = test_files.into_par_iter()
-    .filter_map(|entry| entry.ok().map(|x| x.path()))
-    .collect::<Vec<_>>();
+  // Collect and Sort Test Files, Including Subdirectories
+  let mut test_files = vec![];
+  collect_files(&opt.inpath, &mut test_files);
+  test_files.retain(|path| is_selected(path, &opt));
   test_files.par_sort_unstable();
   let test_count = test_files.len() as u64;
 
 
   // Find Expected Return
   let (test_files, ill_formed): (Vec<_>, Vec<_>) = test_files.into_par_iter()
-    .filter(|path| {
-      match path.extension() { None => false, Some(x) => x != "h0" }
-    }).partition_map(|entry| match expected_res(&entry) {
+    .partition_map(|entry| match test_header(&entry, opt.abs) {
       None => Either::Right(entry),
       Some(res) => Either::Left((entry, res)),
     });
@@ -135,7 +361,7 @@ fn main() {
   // Set Up Progress Bar
   let pb = ProgressBar::new(test_files.len() as u64);
   pb.set_style(ProgressStyle::default_bar()
-    .template("({elapsed_precise}) {pos}/{len} [{bar:60.blue}] {wide_msg}")
+    .template("({elapsed_precise}) {pos}/{len} [{bar:60.blue}] {wide_msg}").unwrap()
     .progress_chars("##-"));
 
   // Set Up Thread to Collect and Modify Progress Bar
@@ -168,71 +394,31 @@ fn main() {
 
   // Run Each Test Case
   let test_count = test_files.len();
-  let (timeout_tests, failed_tests): (Vec<_>, Vec<_>) = test_files.into_par_iter()
+  let results: Vec<_> = test_files.into_par_iter()
     .enumerate()
-    .map_with(tx, |tx, (idx, (path, expec))| {
+    .map_with(tx, |tx, (idx, (path, header))| {
       let path_str = path.as_path().file_name().unwrap().to_str().unwrap().to_string();
       let _ = tx.send(Passing::Starting(idx, path_str));
+      let test_time = Instant::now();
 
-      // std::thread::sleep(std::time::Duration::from_secs(1));
       // Print Basic Info
-      // println!("Running `{}` expecting {}", path.display(), expec);
+      // println!("Running `{}` expecting {}", path.display(), header.expected);
 
-      let mut compiler = Command::new(opt.bin_path.as_os_str().to_str().unwrap());
-      compiler.arg(path.clone());
-
-      let succ = match expec {
-        TestCase::Error => {
-          let output = compiler.arg("-t").output()
-            .expect("Failed to run compiler");
-
-          // println!("{} {}", String::from_utf8(output.stdout).unwrap(), String::from_utf8(output.stderr).unwrap());
-          if !output.status.success() { None } else { Some(true) }
-        },
-
-        TestCase::Return(val) => exec(compiler, &path, ReturnType::Return(val)),
-        TestCase::DivByZero => exec(compiler, &path, ReturnType::DivByZero),
-
-        TestCase::Typecheck => None,
+      let expected = header.expected.to_string();
+      let category = header.expected.category();
+      let graded = if opt.abs {
+        grade_abs(&path, header.expected.clone(), header.run_timeout.unwrap_or(opt.run_timeout))
+      } else {
+        grade_compiled(&path, &header, &opt)
       };
 
       let _ = tx.send(Passing::Ending(idx));
-
-      // let res = match succ {
-      //   // Some(true) => {
-      //   true => {
-      //     // if opt.quiet != Quiet::Fails {
-      //       // pb.println("test");
-      //       // println!("\x1b[92m-- PASS: {:?} --\x1b[0m", path);
-      //     // }
-      //     None
-      //   },
-      //   // Some(false) => {
-      //   //   println!("\x1b[1m\x1b[93m-- TIME: {:?} --\x1b[0m", path);
-      //   //   Some((path, true))
-      //   // },
-      //   //None => {
-      //   false => {
-      //     // println!("\x1b[1m\x1b[91m-- FAIL: {:?} --\x1b[0m", path, );
-      //     Some((path, false))
-      //   },
-      // };
-
-
-      succ.map(|val| (path, val))
+      TestResult { lab: lab(&path), path, category, expected, graded, time: test_time.elapsed().as_secs_f64() }
     })
+    .collect();
 
-    .filter_map(|x| x)
-    .partition_map(|(path, is_fail)| {
-      if is_fail {
-        Either::Right(path)
-      } else {
-        Either::Left(path)
-      }
-    });
-
-  let failed_count = failed_tests.len();
-  let timeout_count = timeout_tests.len();
+  let failed_count = results.iter().filter(|result| result.graded.status == Status::Fail).count();
+  let timeout_count = results.iter().filter(|result| result.graded.status == Status::Timeout).count();
 
   // Join Handler
   let _ = child.join();
@@ -246,17 +432,25 @@ fn main() {
     }
   }
 
-  if !timeout_tests.is_empty() {
+  if timeout_count > 0 {
     println!("-- Timeout Tests --");
-    for file in timeout_tests {
-      println!("  {}", file.display())
+    for result in results.iter().filter(|result| result.graded.status == Status::Timeout) {
+      println!("  {}", result.path.display())
     }
   }
 
-  if !failed_tests.is_empty() {
+  if failed_count > 0 {
     println!("-- Failed Tests --");
-    for file in failed_tests {
-      println!("  {}", file.display())
+    for result in results.iter().filter(|result| result.graded.status == Status::Fail) {
+      println!("  {}", result.path.display())
+    }
+  }
+
+  // Why Each Test Failed, Unless Only the Failing Files are Wanted
+  if opt.quiet == Quiet::LinePerFile && failed_count + timeout_count > 0 {
+    println!("-- Failure Details --");
+    for result in results.iter().filter(|result| result.graded.status != Status::Pass) {
+      println!("{}", result.details().lines().map(|line| format!("  {}", line)).join("\n"));
     }
   }
 
@@ -265,5 +459,152 @@ fn main() {
   println!("-- Failed:  {} / {} --", failed_count, test_count);
   println!("-- Timeout: {} / {} --", timeout_count, test_count);
 
-  // TODO: Add Autograder Calculation
+  // Autograder Score
+  let scheme = Scheme { weights: opt.weights.clone(), threshold: opt.threshold, lab_points: opt.lab_points };
+  let score = Score::new(&results, &scheme);
+  score.print();
+
+  for report in opt.reports.iter() {
+    if let Err(err) = report.write(&results, &score, start_time.elapsed().as_secs_f64()) {
+      eprintln!("Unable to Write Report `{}`: {}", report.path().display(), err);
+    }
+  }
+
+  // Compare Against the Baseline Before it is Overwritten
+  let snapshot = Snapshot::new(&results, &opt.inpath);
+  let comparison = opt.baseline.as_ref().map(|path| match Snapshot::load(path) {
+    Ok(baseline) => Comparison::new(&baseline, &snapshot),
+    Err(err) => {
+      eprintln!("Unable to Read Baseline `{}`: {}", path.display(), err);
+      process::exit(2);
+    },
+  });
+
+  if let Some(path) = &opt.save_baseline {
+    if let Err(err) = snapshot.save(path) {
+      eprintln!("Unable to Write Baseline `{}`: {}", path.display(), err);
+    }
+  }
+
+  if let Some(comparison) = comparison {
+    comparison.print();
+    if comparison.regressed() {
+      process::exit(1);
+    }
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Header of a Test Whose First Line is `line`
+  fn header(line: &str, abs: bool) -> Option<Header> {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file, "{}", line).unwrap();
+    test_header(&file.path().to_path_buf(), abs)
+  }
+
+  #[test]
+  fn header_results() {
+    assert_eq!(header("//test return 5", false).map(|h| h.expected), Some(TestCase::Return(5)));
+    assert_eq!(header("//test return ~5;", false).map(|h| h.expected), Some(TestCase::Return(-5)));
+    assert_eq!(header("//test return ~2147483648", false).map(|h| h.expected), Some(TestCase::Return(i32::MIN)));
+    assert_eq!(header("//test div-by-zero", false).map(|h| h.expected), Some(TestCase::DivByZero));
+    assert!(header("//test return five", false).is_none());
+
+    // Bare Headers Only in Abstract Assembly Files
+    assert_eq!(header("// return 5", true).map(|h| h.expected), Some(TestCase::Return(5)));
+    assert!(header("// return 5", false).is_none());
+  }
+
+  #[test]
+  fn header_timeouts() {
+    let h = header("//test return 5 run-timeout=20 compile-timeout=120", false).unwrap();
+    assert_eq!((h.expected, h.run_timeout, h.compile_timeout), (TestCase::Return(5), Some(20), Some(120)));
+
+    let h = header("//test run-timeout=3 error", false).unwrap();
+    assert_eq!((h.expected, h.run_timeout, h.compile_timeout), (TestCase::Error, Some(3), None));
+
+    // Unknown Keys and Invalid Values Reject the Header
+    assert!(header("//test return 5 timeout=20", false).is_none());
+    assert!(header("//test return 5 run-timeout=soon", false).is_none());
+  }
+
+  #[test]
+  fn output_names() {
+    let path = Path::new("/tmp/work/add.l2");
+    assert_eq!(output_path(path, "{file}.abs"), Path::new("/tmp/work/add.l2.abs"));
+    assert_eq!(output_path(path, "{stem}.s"), Path::new("/tmp/work/add.s"));
+    assert_eq!(output_path(path, "out/{stem}-{file}"), Path::new("/tmp/work/out/add-add.l2"));
+    assert_eq!(output_path(path, "a.out"), Path::new("/tmp/work/a.out"));
+  }
+
+  #[test]
+  fn artifacts_are_kept() {
+    let workspace = tempfile::tempdir().unwrap();
+    fs::write(workspace.path().join("add.l2"), "int main() { return 1; }").unwrap();
+    fs::write(workspace.path().join("add.l2.abs"), "main\n  @0\n    ret 1\n").unwrap();
+    fs::create_dir(workspace.path().join("build")).unwrap();
+
+    // Files Left from an Earlier Run are Removed
+    let keep_dir = tempfile::tempdir().unwrap();
+    let dest = keep_dir.path().join("l2/add.l2");
+    fs::create_dir_all(&dest).unwrap();
+    fs::write(dest.join("stale.abs"), "").unwrap();
+
+    let run = CompilerRun { exit_code: Some(0), signal: None, timed_out: false, stderr: "warning: unused".to_string() };
+    keep_artifacts(workspace.path(), &dest, &Graded::fail(Reason::WrongResult, "return 2").with_compiler(run)).unwrap();
+    let kept = fs::read_dir(&dest).unwrap().map(|entry| entry.unwrap().file_name().into_string().unwrap()).sorted().collect::<Vec<_>>();
+    assert_eq!(kept, ["add.l2", "add.l2.abs", "compiler.stderr"]);
+    assert_eq!(fs::read_to_string(dest.join("compiler.stderr")).unwrap(), "warning: unused");
+  }
+
+  #[test]
+  fn compiler_failures() {
+    let dir = tempfile::tempdir().unwrap();
+    let opt = Options::from_iter(["runner", "--error-exit-code", "2", dir.path().to_str().unwrap()]);
+    let failure = |exit_code, signal, timed_out| {
+      let graded = compile_failure(CompilerRun { exit_code, signal, timed_out, stderr: String::new() }, &opt);
+      (graded.status, graded.reason.unwrap(), graded.actual.unwrap())
+    };
+
+    assert_eq!(failure(Some(2), None, false), (Status::Fail, Reason::CompilerRejected, "error".to_string()));
+    assert_eq!(failure(Some(1), None, false), (Status::Fail, Reason::CompilerInternalError, "exit status 1".to_string()));
+    assert_eq!(failure(Some(101), None, false), (Status::Fail, Reason::CompilerInternalError, "exit status 101".to_string()));
+    assert_eq!(failure(None, Some(11), false), (Status::Fail, Reason::CompilerCrash, "crash".to_string()));
+    assert_eq!(failure(None, None, true), (Status::Timeout, Reason::CompilerTimeout, "timeout".to_string()));
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn compiler_runs() {
+    let (success, run) = compile(Command::new("sh").args(["-c", "echo oops >&2; exit 3"]), 10);
+    assert!(!success);
+    assert_eq!((run.exit_code, run.signal, run.timed_out, run.stderr.as_str()), (Some(3), None, false, "oops\n"));
+
+    let (success, run) = compile(Command::new("sh").args(["-c", "kill -SEGV $$"]), 10);
+    assert!(!success);
+    assert_eq!((run.exit_code, run.signal, run.timed_out), (None, Some(11), false));
+
+    let (success, run) = compile(Command::new("sleep").arg("10"), 0);
+    assert!(!success);
+    assert_eq!((run.exit_code, run.signal, run.timed_out), (None, None, true));
+  }
+
+  #[test]
+  fn examples_pass() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples");
+    let mut files = vec![];
+    collect_files(&examples, &mut files);
+    files.retain(|path| path.extension().is_some_and(|ext| ext == "abs"));
+    assert!(!files.is_empty());
+
+    for path in files {
+      let header = test_header(&path, true).unwrap();
+      let graded = grade_abs(&path, header.expected, 10);
+      assert!(graded.status == Status::Pass, "`{}` failed: {:?}", path.display(), graded.reason);
+    }
+  }
 }
@@ -0,0 +1,194 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::result::{Status, TestResult};
+use crate::score::Score;
+
+
+/// Machine Readable Report of a Test Run, Given as `<format>=<path>`
+#[derive(Debug, Clone)]
+pub enum Report {
+  JUnit(PathBuf),
+  Json(PathBuf),
+}
+
+impl FromStr for Report {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.split_once('=') {
+      Some(("junit", path)) if !path.is_empty() => Ok(Self::JUnit(PathBuf::from(path))),
+      Some(("json", path)) if !path.is_empty() => Ok(Self::Json(PathBuf::from(path))),
+      _ => Err(format!("Expected `junit=<path>` or `json=<path>`, Found `{}`", s)),
+    }
+  }
+}
+
+impl Report {
+  pub fn path(&self) -> &PathBuf {
+    match self {
+      Self::JUnit(path) | Self::Json(path) => path,
+    }
+  }
+
+  pub fn write(&self, results: &[TestResult], score: &Score, time: f64) -> io::Result<()> {
+    let contents = match self {
+      Self::JUnit(_) => junit(results, time),
+      Self::Json(_) => json(results, score, time),
+    };
+
+    fs::write(self.path(), contents)
+  }
+}
+
+
+fn count(results: &[TestResult], status: Status) -> usize {
+  results.iter().filter(|result| result.graded.status == status).count()
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+  passed: usize,
+  failed: usize,
+  timeout: usize,
+  time: f64,
+  score: &'a Score,
+  tests: &'a [TestResult],
+}
+
+fn json(results: &[TestResult], score: &Score, time: f64) -> String {
+  let report = JsonReport {
+    passed: count(results, Status::Pass),
+    failed: count(results, Status::Fail),
+    timeout: count(results, Status::Timeout),
+    time,
+    score,
+    tests: results,
+  };
+
+  serde_json::to_string_pretty(&report).unwrap()
+}
+
+
+/// Escape Text for XML Attributes and Elements
+fn xml_escape(text: &str) -> String {
+  text.chars().fold(String::new(), |mut out, chr| {
+    match chr {
+      '&' => out.push_str("&amp;"),
+      '<' => out.push_str("&lt;"),
+      '>' => out.push_str("&gt;"),
+      '"' => out.push_str("&quot;"),
+      '\'' => out.push_str("&apos;"),
+      // Not Allowed in XML 1.0, such as the Escape Codes of Colored Output
+      chr if chr.is_control() && !matches!(chr, '\n' | '\r' | '\t') => {},
+      chr => out.push(chr),
+    }
+    out
+  })
+}
+
+/// Report in the JUnit XML Format, with Timeouts as Errors
+fn junit(results: &[TestResult], time: f64) -> String {
+  let mut out = String::new();
+  let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+  let _ = writeln!(
+    out, r#"<testsuite name="runner" tests="{}" failures="{}" errors="{}" time="{:.3}">"#,
+    results.len(), count(results, Status::Fail), count(results, Status::Timeout), time
+  );
+
+  for result in results {
+    let classname = result.path.parent().map_or_else(String::new, |dir| dir.display().to_string());
+    let _ = writeln!(
+      out, r#"  <testcase name="{}" classname="{}" time="{:.3}">"#,
+      xml_escape(&result.name()), xml_escape(&classname), result.time
+    );
+
+    if let Some(compiler) = &result.graded.compiler {
+      let exit_code = match (compiler.exit_code, compiler.signal) {
+        (Some(code), _) => code.to_string(),
+        (None, Some(signal)) => format!("signal {}", signal),
+        (None, None) => "none".to_string(),
+      };
+      let _ = writeln!(out, r#"    <properties><property name="compiler-exit-code" value="{}"/></properties>"#, exit_code);
+    }
+
+    let message = format!("expected {}, got {}", result.expected, result.graded.actual.as_deref().unwrap_or("nothing"));
+    let details = result.graded.diagnostics.as_deref().unwrap_or("");
+    match result.graded.status {
+      Status::Pass => {},
+      Status::Fail => {
+        let reason = result.graded.reason.map_or_else(String::new, |reason| reason.to_string());
+        let _ = writeln!(out, r#"    <failure type="{}" message="{}">{}</failure>"#, xml_escape(&reason), xml_escape(&message), xml_escape(details));
+      },
+      Status::Timeout => {
+        let _ = writeln!(out, r#"    <error type="timeout" message="{}">{}</error>"#, xml_escape(&message), xml_escape(details));
+      },
+    }
+
+    if let Some(compiler) = result.graded.compiler.as_ref().filter(|compiler| !compiler.stderr.is_empty()) {
+      let _ = writeln!(out, "    <system-err>{}</system-err>", xml_escape(&compiler.stderr));
+    }
+
+    let _ = writeln!(out, "  </testcase>");
+  }
+
+  let _ = writeln!(out, "</testsuite>");
+  out
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::result::{Category, Graded, Reason};
+  use crate::score::Scheme;
+
+  fn result(path: &str, graded: Graded) -> TestResult {
+    TestResult { path: path.into(), lab: Some("l1".to_string()), category: Category::Return, expected: "return 0".to_string(), graded, time: 0.5 }
+  }
+
+  #[test]
+  fn xml_is_escaped() {
+    assert_eq!(xml_escape(r#"a < b && "c" > 'd'"#), "a &lt; b &amp;&amp; &quot;c&quot; &gt; &apos;d&apos;");
+
+    // Color Codes are Dropped, but Not Whitespace
+    assert_eq!(xml_escape("\x1b[1merror\x1b[0m\n\tat line 1"), "[1merror[0m\n\tat line 1");
+  }
+
+  #[test]
+  fn junit_statuses() {
+    let results = [
+      result("tests/pass.l1", Graded::pass("return 0")),
+      result("tests/wrong<1>.l1", Graded::fail(Reason::WrongResult, "return 1").with_diagnostics("x & y")),
+      result("tests/slow.l1", Graded::fail(Reason::Timeout, "timeout")),
+    ];
+
+    let report = junit(&results, 1.5);
+    assert!(report.contains(r#"<testsuite name="runner" tests="3" failures="1" errors="1" time="1.500">"#));
+    assert!(report.contains(r#"<testcase name="wrong&lt;1&gt;.l1" classname="tests" time="0.500">"#));
+    assert!(report.contains(r#"<failure type="wrong result" message="expected return 0, got return 1">x &amp; y</failure>"#));
+    assert!(report.contains(r#"<error type="timeout" message="expected return 0, got timeout"></error>"#));
+    assert_eq!(report.matches("<failure").count() + report.matches("<error").count(), 2);
+  }
+
+  #[test]
+  fn json_counts() {
+    let results = [
+      result("tests/pass.l1", Graded::pass("return 0")),
+      result("tests/\"quoted\".l1", Graded::fail(Reason::WrongOutput, "return 0").with_diagnostics("line\nbreak")),
+    ];
+    let score = Score::new(&results, &Scheme { weights: vec![], threshold: 1.0, lab_points: 100.0 });
+
+    let report: serde_json::Value = serde_json::from_str(&json(&results, &score, 1.0)).unwrap();
+    assert_eq!((report["passed"].as_u64(), report["failed"].as_u64(), report["timeout"].as_u64()), (Some(1), Some(1), Some(0)));
+    assert_eq!(report["tests"][1]["path"], "tests/\"quoted\".l1");
+    assert_eq!(report["tests"][1]["reason"], "wrong-output");
+    assert_eq!(report["tests"][1]["diagnostics"], "line\nbreak");
+    assert_eq!(report["score"]["points"], 50.0);
+  }
+}
@@ -0,0 +1,198 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+
+/// How a Test Ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all="kebab-case")]
+pub enum Status {
+  Pass,
+  Fail,
+  Timeout,
+}
+
+impl Display for Status {
+  fn fmt(&self, f: &mut Formatter) -> FmtResult {
+    match self {
+      Self::Pass => write!(f, "pass"),
+      Self::Fail => write!(f, "fail"),
+      Self::Timeout => write!(f, "timeout"),
+    }
+  }
+}
+
+/// Kind of Test, as Weighted by the Score
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all="kebab-case")]
+pub enum Category {
+  /// `error` and `typecheck` tests
+  Error,
+  Return,
+  /// `div-by-zero` and `abort` tests
+  Exception,
+}
+
+/// Why a Test Did Not Pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all="kebab-case")]
+pub enum Reason {
+  /// Killed by a signal, or could not be started at all
+  CompilerCrash,
+  /// Exited with a status other than success or the documented error status
+  CompilerInternalError,
+  /// Exited with the documented error status on a valid program
+  CompilerRejected,
+  CompilerTimeout,
+  /// An `error` test compiled or passed the VM checks
+  Accepted,
+  /// The VM could not parse or check the abstract assembly
+  VmError,
+  WrongResult,
+  WrongOutput,
+  Timeout,
+}
+
+impl Display for Reason {
+  fn fmt(&self, f: &mut Formatter) -> FmtResult {
+    match self {
+      Self::CompilerCrash => write!(f, "compiler crashed"),
+      Self::CompilerInternalError => write!(f, "compiler failed with an internal error"),
+      Self::CompilerRejected => write!(f, "compiler rejected a valid program"),
+      Self::CompilerTimeout => write!(f, "compiler timed out"),
+      Self::Accepted => write!(f, "invalid program was accepted"),
+      Self::VmError => write!(f, "VM error"),
+      Self::WrongResult => write!(f, "wrong result"),
+      Self::WrongOutput => write!(f, "wrong output"),
+      Self::Timeout => write!(f, "timeout"),
+    }
+  }
+}
+
+/// Exit Status and Stderr of a Compiler Run
+#[derive(Debug, Clone, Serialize)]
+pub struct CompilerRun {
+  /// `None` when the compiler was killed by a signal
+  pub exit_code: Option<i32>,
+  pub signal: Option<i32>,
+  /// Whether it was killed for running longer than the compile timeout
+  pub timed_out: bool,
+  pub stderr: String,
+}
+
+/// Result of Grading a Test
+#[derive(Debug, Clone, Serialize)]
+pub struct Graded {
+  pub status: Status,
+  pub reason: Option<Reason>,
+  /// What the test did instead, such as `return 5` or `error`
+  pub actual: Option<String>,
+  pub compiler: Option<CompilerRun>,
+  /// Errors reported by the VM
+  pub diagnostics: Option<String>,
+}
+
+impl Graded {
+  pub fn pass(actual: impl ToString) -> Self {
+    Graded { status: Status::Pass, reason: None, actual: Some(actual.to_string()), compiler: None, diagnostics: None }
+  }
+
+  pub fn fail(reason: Reason, actual: impl ToString) -> Self {
+    let status = match reason {
+      Reason::Timeout | Reason::CompilerTimeout => Status::Timeout,
+      _ => Status::Fail,
+    };
+    Graded { status, reason: Some(reason), ..Self::pass(actual) }
+  }
+
+  pub fn with_diagnostics(self, diagnostics: impl ToString) -> Self {
+    Graded { diagnostics: Some(diagnostics.to_string()), ..self }
+  }
+
+  pub fn with_compiler(self, compiler: CompilerRun) -> Self {
+    Graded { compiler: Some(compiler), ..self }
+  }
+}
+
+/// Result of a Test, as Written to Reports
+#[derive(Debug, Clone, Serialize)]
+pub struct TestResult {
+  pub path: PathBuf,
+  pub lab: Option<String>,
+  pub category: Category,
+  pub expected: String,
+  #[serde(flatten)]
+  pub graded: Graded,
+  /// Seconds it took to compile and run the test
+  pub time: f64,
+}
+
+impl TestResult {
+  /// Name of the test file, without its directory
+  pub fn name(&self) -> String {
+    self.path.file_name().map_or_else(|| self.path.display().to_string(), |name| name.to_string_lossy().to_string())
+  }
+
+  /// Why the test failed, with the errors of the VM and (if it failed) of the compiler
+  pub fn details(&self) -> String {
+    let Graded { reason, actual, compiler, diagnostics, .. } = &self.graded;
+    let mut lines = vec![
+      format!("{}: {}", self.path.display(), reason.map_or("passed".to_string(), |reason| reason.to_string())),
+      format!("  expected {}, got {}", self.expected, actual.as_deref().unwrap_or("nothing")),
+    ];
+
+    if let Some(diagnostics) = diagnostics {
+      lines.extend(diagnostics.lines().map(|line| format!("  {}", line)));
+    }
+
+    let compiler_failed = matches!(
+      reason,
+      Some(Reason::CompilerCrash | Reason::CompilerInternalError | Reason::CompilerRejected | Reason::CompilerTimeout)
+    );
+    if let (true, Some(compiler)) = (compiler_failed, compiler) {
+      match (compiler.exit_code, compiler.signal) {
+        (Some(code), _) => lines.push(format!("  compiler exited with status {}", code)),
+        (None, _) if compiler.timed_out => lines.push("  compiler was killed after running too long".to_string()),
+        (None, Some(signal)) => lines.push(format!("  compiler was killed by signal {}", signal)),
+        (None, None) => lines.push("  compiler could not be run".to_string()),
+      }
+      lines.extend(compiler.stderr.lines().map(|line| format!("  | {}", line)));
+    }
+
+    lines.join("\n")
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn result(graded: Graded) -> TestResult {
+    TestResult { path: "l1/test.l1".into(), lab: None, category: Category::Return, expected: "return 5".to_string(), graded, time: 0.0 }
+  }
+
+  fn compiler(exit_code: Option<i32>, signal: Option<i32>, timed_out: bool) -> CompilerRun {
+    CompilerRun { exit_code, signal, timed_out, stderr: "note: line 1\nnote: line 2".to_string() }
+  }
+
+  #[test]
+  fn details_of_failures() {
+    let details = result(Graded::fail(Reason::WrongResult, "return 4").with_diagnostics("first\nsecond")).details();
+    assert_eq!(details, "l1/test.l1: wrong result\n  expected return 5, got return 4\n  first\n  second");
+
+    // The Compiler is Only Shown when it Failed
+    let details = result(Graded::fail(Reason::WrongResult, "return 4").with_compiler(compiler(Some(0), None, false))).details();
+    assert!(!details.contains("compiler") && !details.contains("note"));
+
+    let details = result(Graded::fail(Reason::CompilerInternalError, "exit status 2").with_compiler(compiler(Some(2), None, false))).details();
+    assert!(details.ends_with("  compiler exited with status 2\n  | note: line 1\n  | note: line 2"));
+
+    let details = result(Graded::fail(Reason::CompilerCrash, "crash").with_compiler(compiler(None, Some(11), false))).details();
+    assert!(details.contains("  compiler was killed by signal 11\n"));
+
+    let details = result(Graded::fail(Reason::CompilerTimeout, "timeout").with_compiler(compiler(None, None, true))).details();
+    assert!(details.contains("  compiler was killed after running too long\n"));
+  }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::result::{Category, Status, TestResult};
+
+
+/// Points of One Test in a Category, Given as `<category>=<points>`
+#[derive(Debug, Clone, Copy)]
+pub struct Weight(pub Category, pub f64);
+
+impl FromStr for Weight {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (category, points) = s.split_once('=').ok_or_else(|| format!("Expected `<category>=<points>`, Found `{}`", s))?;
+    let category = match category {
+      "error" => Category::Error,
+      "return" => Category::Return,
+      "exception" => Category::Exception,
+      _ => return Err(format!("Unknown Category `{}`, Expected `error`, `return` or `exception`", category)),
+    };
+    let points = points.parse().map_err(|_| format!("Invalid Points `{}`", points))?;
+    Ok(Weight(category, points))
+  }
+}
+
+/// How Tests are Turned into Points
+pub struct Scheme {
+  pub weights: Vec<Weight>,
+  /// Fraction of the weighted tests of a lab that must pass for full points
+  pub threshold: f64,
+  pub lab_points: f64,
+}
+
+impl Scheme {
+  fn weight(&self, category: Category) -> f64 {
+    self.weights.iter().rev().find(|Weight(cat, _)| *cat == category).map_or(1.0, |Weight(_, points)| *points)
+  }
+}
+
+
+/// Passed and Total Tests of a Category
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Count {
+  pub passed: usize,
+  pub total: usize,
+}
+
+impl Display for Count {
+  fn fmt(&self, f: &mut Formatter) -> FmtResult {
+    write!(f, "{}/{}", self.passed, self.total)
+  }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LabScore {
+  pub error: Count,
+  #[serde(rename="return")]
+  pub ret: Count,
+  pub exception: Count,
+  pub points: f64,
+  pub max_points: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Score {
+  pub labs: BTreeMap<String, LabScore>,
+  pub points: f64,
+  pub max_points: f64,
+}
+
+impl Score {
+  /// Score Every Lab: its Weighted Pass Rate is Divided by the Threshold and Capped at Full Points
+  /// Labs whose tests are all worth nothing are not counted towards the total
+  pub fn new(results: &[TestResult], scheme: &Scheme) -> Self {
+    let mut labs = BTreeMap::new();
+    for result in results {
+      let lab = result.lab.clone().unwrap_or_else(|| "other".to_string());
+      let score = labs.entry(lab).or_insert(LabScore {
+        error: Count::default(), ret: Count::default(), exception: Count::default(), points: 0.0, max_points: scheme.lab_points,
+      });
+
+      let count = match result.category {
+        Category::Error => &mut score.error,
+        Category::Return => &mut score.ret,
+        Category::Exception => &mut score.exception,
+      };
+      count.total += 1;
+      count.passed += (result.graded.status == Status::Pass) as usize;
+    }
+
+    for score in labs.values_mut() {
+      let counts = [(Category::Error, score.error), (Category::Return, score.ret), (Category::Exception, score.exception)];
+      let weighted = |count: fn(&Count) -> usize| counts.iter()
+        .map(|(category, cnt)| scheme.weight(*category) * count(cnt) as f64)
+        .sum::<f64>();
+
+      let (passed, total) = (weighted(|cnt| cnt.passed), weighted(|cnt| cnt.total));
+      if total <= 0.0 {
+        score.max_points = 0.0;
+        continue;
+      }
+
+      let rate = passed / total;
+      score.points = if scheme.threshold > 0.0 { (rate / scheme.threshold).min(1.0) } else { 1.0 } * scheme.lab_points;
+    }
+
+    let points = labs.values().map(|score| score.points).sum();
+    let max_points = labs.values().map(|score| score.max_points).sum();
+    Score { labs, points, max_points }
+  }
+
+  pub fn print(&self) {
+    println!("-- Score --");
+    for (lab, score) in self.labs.iter() {
+      println!(
+        "  {}: error {}, return {}, exception {} -> {:.1} / {:.1}",
+        lab, score.error, score.ret, score.exception, score.points, score.max_points
+      );
+    }
+    println!("-- Score: {:.1} / {:.1} --", self.points, self.max_points);
+  }
+}
+
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::result::{Graded, Reason};
+
+  fn result(lab: Option<&str>, category: Category, passed: bool) -> TestResult {
+    TestResult {
+      path: "test".into(),
+      lab: lab.map(str::to_string),
+      category,
+      expected: "return 0".to_string(),
+      graded: if passed { Graded::pass("return 0") } else { Graded::fail(Reason::WrongResult, "return 1") },
+      time: 0.0,
+    }
+  }
+
+  fn scheme(weights: Vec<Weight>, threshold: f64) -> Scheme {
+    Scheme { weights, threshold, lab_points: 100.0 }
+  }
+
+  #[test]
+  fn weighted_rate_over_threshold() {
+    let results = [
+      result(Some("l1"), Category::Error, true),
+      result(Some("l1"), Category::Error, false),
+      result(Some("l1"), Category::Return, true),
+      result(Some("l1"), Category::Return, true),
+    ];
+
+    // Unweighted: 3 of 4 Pass, Weighted: 4 of 6
+    let score = Score::new(&results, &scheme(vec![], 1.0));
+    assert!((score.labs["l1"].points - 75.0).abs() < 1e-9);
+
+    let score = Score::new(&results, &scheme(vec!["error=2".parse().unwrap()], 0.8));
+    assert!((score.labs["l1"].points - 4.0 / 6.0 / 0.8 * 100.0).abs() < 1e-9);
+    assert_eq!((score.labs["l1"].error.passed, score.labs["l1"].error.total), (1, 2));
+
+    // Capped at Full Points Once the Threshold is Reached
+    let score = Score::new(&results, &scheme(vec![], 0.7));
+    assert_eq!(score.labs["l1"].points, 100.0);
+
+    // Every Lab Gets Full Points Without a Threshold
+    let score = Score::new(&results, &scheme(vec![], 0.0));
+    assert_eq!(score.labs["l1"].points, 100.0);
+  }
+
+  #[test]
+  fn total_over_labs() {
+    let results = [
+      result(Some("l1"), Category::Return, true),
+      result(Some("l2"), Category::Exception, false),
+      result(Some("l3"), Category::Error, true),
+      result(None, Category::Return, true),
+    ];
+
+    // Labs Whose Tests are Worth Nothing Do Not Count
+    let score = Score::new(&results, &scheme(vec!["error=0".parse().unwrap()], 0.9));
+    let points = score.labs.iter().map(|(lab, score)| (lab.as_str(), score.points, score.max_points)).collect::<Vec<_>>();
+    assert_eq!(points, [("l1", 100.0, 100.0), ("l2", 0.0, 100.0), ("l3", 0.0, 0.0), ("other", 100.0, 100.0)]);
+    assert_eq!((score.points, score.max_points), (200.0, 300.0));
+
+    // No Tests, No Labs
+    let score = Score::new(&[], &scheme(vec![], 0.9));
+    assert!(score.labs.is_empty());
+    assert_eq!((score.points, score.max_points), (0.0, 0.0));
+  }
+
+  #[test]
+  fn later_weights_win() {
+    let weight = |s: &str| s.parse::<Weight>().unwrap();
+    let scheme = scheme(vec![weight("return=2"), weight("return=3")], 0.9);
+    assert_eq!(scheme.weight(Category::Return), 3.0);
+    assert_eq!(scheme.weight(Category::Error), 1.0);
+    assert!("loops=2".parse::<Weight>().is_err());
+  }
+}